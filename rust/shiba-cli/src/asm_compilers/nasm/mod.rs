@@ -2,13 +2,14 @@ mod settings;
 
 pub use self::settings::NasmSettings;
 use crate::build::BuildOptions;
+use crate::build_cache;
 use crate::compilation::{Platform, PlatformDependent};
 use crate::compilation_data::Linking;
 use crate::compilers::{CompileOptions, Compiler};
-use crate::hash_extra;
-use crate::paths::BUILD_ROOT_DIRECTORY;
+use crate::hash_extra::{self, ToolIdentity};
 use crate::project_data::Project;
 use crate::{Error, Result};
+use serde::Serialize;
 use std::collections::BTreeSet;
 use std::fs;
 use std::path::PathBuf;
@@ -18,16 +19,19 @@ pub struct NasmCompiler<'a> {
 	settings: &'a NasmSettings,
 
 	nasm_path: PathBuf,
+	nasm_tool: ToolIdentity,
 }
 
 impl<'a> NasmCompiler<'a> {
 	pub fn new(project: &'a Project, settings: &'a NasmSettings) -> Result<Self> {
 		let nasm_path = project.configuration.get_path("nasm");
+		let nasm_tool = hash_extra::get_tool_identity(&nasm_path);
 
 		Ok(NasmCompiler {
 			settings,
 
 			nasm_path,
+			nasm_tool,
 		})
 	}
 }
@@ -51,33 +55,38 @@ impl<'a> Compiler for NasmCompiler<'a> {
 		let contents = fs::read_to_string(options.path)
 			.map_err(|err| Error::failed_to_read(options.path, err))?;
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
 			contents: &'a str,
+			nasm_tool: &'a ToolIdentity,
 			options: &'a CompileOptions<'a>,
 			settings: &'a NasmSettings,
 		}
 
 		let inputs = Inputs {
 			contents: &contents,
+			nasm_tool: &self.nasm_tool,
 			options,
 			settings: self.settings,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "asm-compilers/nasm", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(output_filename);
 
 		linking.sources.push(build_cache_path.clone());
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit_for_source(&build_cache_path, options.path) {
 			return Ok(());
 		}
 
-		let build_directory = BUILD_ROOT_DIRECTORY.join("cpp-compilers").join("nasm");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let mut command = Command::new(&self.nasm_path);
-		command.args(vec!["-f", "win32"]);
+		command.arg("-f").arg(match options.platform {
+			Platform::X64 => "win64",
+			Platform::X86 => "win32",
+		});
 
 		for path in options.include_paths {
 			command.arg("-i").arg(path);
@@ -99,8 +108,7 @@ impl<'a> Compiler for NasmCompiler<'a> {
 		}
 
 		let copy_from = build_directory.join("file.obj");
-		fs::copy(&copy_from, &build_cache_path)
-			.map_err(|err| Error::failed_to_copy(&copy_from, &build_cache_path, err))?;
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
 
 		Ok(())
 	}
@@ -111,6 +119,7 @@ impl PlatformDependent for NasmCompiler<'_> {
 		lazy_static! {
 			pub static ref POSSIBLE_PLATFORMS: BTreeSet<Platform> = {
 				let mut possible_platforms = BTreeSet::new();
+				possible_platforms.insert(Platform::X64);
 				possible_platforms.insert(Platform::X86);
 				possible_platforms
 			};
@@ -2,15 +2,24 @@ use super::nasm;
 use crate::compilers::Compiler;
 use crate::project_data::Project;
 use crate::Result;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "AsmCompilerSettings")]
 #[serde(rename_all = "kebab-case", tag = "tool")]
 pub enum Settings {
 	Nasm(nasm::NasmSettings),
 }
 
 impl Settings {
+	/// Returns the names of the tools to configure.
+	pub fn get_tool_names(&self) -> Vec<&'static str> {
+		match self {
+			Settings::Nasm(_) => vec!["nasm"],
+		}
+	}
+
 	pub fn instantiate<'a>(&'a self, project: &'a Project) -> Result<Box<(dyn Compiler + 'a)>> {
 		let instance: Box<(dyn Compiler + 'a)> = match self {
 			Settings::Nasm(settings) => Box::new(nasm::NasmCompiler::new(project, settings)?),
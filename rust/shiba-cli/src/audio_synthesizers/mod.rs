@@ -3,17 +3,24 @@ pub mod oidos;
 mod settings;
 
 use crate::build::BuildOptions;
-use crate::compilation::CompilationJobEmitter;
+use crate::compilation::{CompilationJobEmitter, PlatformDependent, System};
 use crate::compilation_data::Compilation;
 use crate::project_files::CodeMap;
 use crate::project_files::FileConsumer;
 use crate::Result;
+use serde::Serialize;
 pub use settings::Settings;
 
-pub trait AudioSynthesizer: FileConsumer + CompilationJobEmitter {
+#[derive(Serialize)]
+pub struct IntegrationOptions {
+	pub system: System,
+}
+
+pub trait AudioSynthesizer: FileConsumer + CompilationJobEmitter + PlatformDependent {
 	fn integrate(
 		&self,
 		build_options: &BuildOptions,
+		options: &IntegrationOptions,
 		compilation: &mut Compilation,
 	) -> Result<CodeMap>;
 }
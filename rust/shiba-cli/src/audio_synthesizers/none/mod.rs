@@ -1,15 +1,16 @@
 mod settings;
 
 pub use self::settings::NoneSettings;
-use super::AudioSynthesizer;
+use super::{AudioSynthesizer, IntegrationOptions};
 use crate::build::BuildOptions;
-use crate::compilation::CompilationJobEmitter;
+use crate::compilation::{CompilationJobEmitter, Platform, PlatformDependent, System};
 use crate::compilation_data::Compilation;
 use crate::project_data::Project;
 use crate::project_files::{CodeMap, FileConsumer, IsPathHandled};
 use crate::{Error, Result};
 use ordered_float::OrderedFloat;
 use serde::Serialize;
+use std::collections::BTreeSet;
 use tera::{Context, Tera};
 
 template_enum! {
@@ -41,15 +42,18 @@ impl<'a> AudioSynthesizer for NoneAudioSynthesizer<'a> {
 	fn integrate(
 		&self,
 		_build_options: &BuildOptions,
+		options: &IntegrationOptions,
 		_compilation: &mut Compilation,
 	) -> Result<CodeMap> {
 		#[derive(Serialize)]
 		struct OwnContext {
 			speed: Option<OrderedFloat<f32>>,
+			system: System,
 		}
 
 		let context = OwnContext {
 			speed: self.settings.speed,
+			system: options.system,
 		};
 
 		let mut codes = CodeMap::default();
@@ -83,3 +87,18 @@ impl FileConsumer for NoneAudioSynthesizer<'_> {
 		Box::new(|_path| false)
 	}
 }
+
+impl PlatformDependent for NoneAudioSynthesizer<'_> {
+	fn get_possible_platforms(&self) -> &'static BTreeSet<Platform> {
+		lazy_static! {
+			pub static ref POSSIBLE_PLATFORMS: BTreeSet<Platform> = {
+				let mut possible_platforms = BTreeSet::new();
+				possible_platforms.insert(Platform::X64);
+				possible_platforms.insert(Platform::X86);
+				possible_platforms
+			};
+		}
+
+		&*POSSIBLE_PLATFORMS
+	}
+}
@@ -1,9 +1,11 @@
 use ordered_float::OrderedFloat;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Deserialize, Hash)]
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct NoneSettings {
+	#[schemars(with = "Option<f32>")]
 	#[serde(default)]
 	pub speed: Option<OrderedFloat<f32>>,
 }
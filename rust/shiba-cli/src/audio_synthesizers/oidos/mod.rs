@@ -1,12 +1,12 @@
 mod settings;
 
 pub use self::settings::OidosSettings;
-use super::AudioSynthesizer;
+use super::{AudioSynthesizer, IntegrationOptions};
 use crate::build::BuildOptions;
-use crate::compilation::CompilationJobEmitter;
+use crate::build_cache;
+use crate::compilation::{CompilationJobEmitter, Platform, PlatformDependent, System};
 use crate::compilation_data::{Compilation, CompilationJob, CompilationJobKind};
-use crate::hash_extra;
-use crate::paths::BUILD_ROOT_DIRECTORY;
+use crate::hash_extra::{self, ToolIdentity};
 use crate::project_data::Project;
 use crate::project_files::{CodeMap, FileConsumer, IsPathHandled};
 use crate::{Error, Result};
@@ -15,7 +15,7 @@ use serde_json;
 use std::borrow::Cow;
 use std::collections::BTreeSet;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::process::Command;
 use tera::{Context, Tera};
 
@@ -30,16 +30,21 @@ template_enum! {
 pub struct OidosAudioSynthesizer<'a> {
 	settings: &'a OidosSettings,
 
+	convert_tool: ToolIdentity,
 	oidos_path: PathBuf,
 	python2_path: PathBuf,
+	python2_tool: ToolIdentity,
 	tera: Tera,
 }
 
 impl<'a> OidosAudioSynthesizer<'a> {
 	pub fn new(project: &'a Project, settings: &'a OidosSettings) -> Result<Self> {
 		let oidos_path = project.configuration.get_path("oidos");
+		let convert_tool =
+			hash_extra::get_tool_identity(&oidos_path.join("convert").join("OidosConvert.py"));
 
 		let python2_path = project.configuration.get_path("python2");
+		let python2_tool = hash_extra::get_tool_identity(&python2_path);
 
 		let mut tera = Tera::default();
 
@@ -49,8 +54,10 @@ impl<'a> OidosAudioSynthesizer<'a> {
 		Ok(OidosAudioSynthesizer {
 			settings,
 
+			convert_tool,
 			oidos_path,
 			python2_path,
+			python2_tool,
 			tera,
 		})
 	}
@@ -60,10 +67,15 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 	fn integrate(
 		&self,
 		build_options: &BuildOptions,
+		options: &IntegrationOptions,
 		compilation: &mut Compilation,
 	) -> Result<CodeMap> {
 		const OUTPUT_FILENAME: &str = "codes.json";
 
+		if options.system != System::Windows {
+			return Err(Error::message("Oidos only supports Windows."));
+		}
+
 		let mut path = Cow::from(&self.settings.path);
 		if path.is_relative() {
 			path = Cow::from(build_options.project.directory.join(path));
@@ -71,21 +83,23 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 
 		let contents = fs::read(&path).map_err(|err| Error::failed_to_read(&path.as_ref(), err))?;
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
 			contents: &'a [u8],
-			oidos_path: &'a Path,
-			python2_path: &'a Path,
+			convert_tool: &'a ToolIdentity,
+			python2_tool: &'a ToolIdentity,
 			settings: &'a OidosSettings,
 		}
 
 		let inputs = Inputs {
 			contents: &contents,
-			oidos_path: &self.oidos_path,
-			python2_path: &self.python2_path,
+			convert_tool: &self.convert_tool,
+			python2_tool: &self.python2_tool,
 			settings: self.settings,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "audio-synthesizers/oidos", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
 
 		compilation
@@ -94,14 +108,14 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 		compilation
 			.common
 			.link_library_paths
-			.insert(build_cache_directory.clone());
+			.insert(build_cache_directory.to_path_buf());
 		compilation
 			.common
 			.link_dependencies
 			.insert(PathBuf::from("winmm.lib"));
 
 		let mut include_paths = BTreeSet::new();
-		include_paths.insert(build_cache_directory.clone());
+		include_paths.insert(build_cache_directory.to_path_buf());
 
 		for path in &["oidos.asm", "random.asm"] {
 			compilation.jobs.push(CompilationJob {
@@ -111,7 +125,7 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 			});
 		}
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			let json = fs::read_to_string(&build_cache_path)
 				.map_err(|err| Error::failed_to_read(&build_cache_path, err))?;
 			let codes = serde_json::from_str(&json)
@@ -119,11 +133,7 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 			return Ok(codes);
 		}
 
-		let build_directory = BUILD_ROOT_DIRECTORY
-			.join("audio-synthesizers")
-			.join("oidos");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let mut conversion = Command::new(&self.python2_path)
 			.arg(
@@ -146,8 +156,7 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 
 		let copy_from = build_directory.join("music.asm");
 		let copy_to = build_cache_directory.join("music.asm");
-		fs::copy(&copy_from, &copy_to)
-			.map_err(|err| Error::failed_to_copy(&copy_from, &copy_to, err))?;
+		build_cache::copy_atomically(&copy_from, &copy_to)?;
 
 		#[derive(Serialize)]
 		struct OwnContext {}
@@ -167,8 +176,7 @@ impl<'a> AudioSynthesizer for OidosAudioSynthesizer<'a> {
 		}
 
 		let json = serde_json::to_string(&codes).expect("Failed to dump JSON.");
-		fs::write(&build_cache_path, json)
-			.map_err(|err| Error::failed_to_write(&build_cache_path, err))?;
+		build_cache::write_atomically(&build_cache_path, json)?;
 
 		Ok(codes)
 	}
@@ -189,3 +197,18 @@ impl FileConsumer for OidosAudioSynthesizer<'_> {
 		Box::new(move |path| path == self.settings.path)
 	}
 }
+
+impl PlatformDependent for OidosAudioSynthesizer<'_> {
+	fn get_possible_platforms(&self) -> &'static BTreeSet<Platform> {
+		lazy_static! {
+			pub static ref POSSIBLE_PLATFORMS: BTreeSet<Platform> = {
+				// The replay code is 32-bit assembly.
+				let mut possible_platforms = BTreeSet::new();
+				possible_platforms.insert(Platform::X86);
+				possible_platforms
+			};
+		}
+
+		&*POSSIBLE_PLATFORMS
+	}
+}
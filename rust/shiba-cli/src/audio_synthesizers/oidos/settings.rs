@@ -1,11 +1,12 @@
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 fn default_path() -> PathBuf {
 	PathBuf::from("music.xrns")
 }
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct OidosSettings {
 	#[serde(default = "default_path")]
@@ -2,9 +2,11 @@ use super::{none, oidos, AudioSynthesizer};
 use crate::build::BuildTarget;
 use crate::project_data::Project;
 use crate::Result;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "AudioSynthesizerSettings")]
 #[serde(rename_all = "kebab-case", tag = "tool")]
 pub enum Settings {
 	None(none::NoneSettings),
@@ -12,6 +14,14 @@ pub enum Settings {
 }
 
 impl Settings {
+	/// Returns the names of the tools to configure.
+	pub fn get_tool_names(&self) -> Vec<&'static str> {
+		match self {
+			Settings::None(_) => vec![],
+			Settings::Oidos(_) => vec!["oidos", "python2"],
+		}
+	}
+
 	pub fn instantiate<'a>(
 		&'a self,
 		project: &'a Project,
@@ -1,19 +1,32 @@
-use crate::compilation_data::{Compilation, CompilationJobKind, Linking};
-use crate::compilers::CompileOptions;
+use crate::audio_synthesizers::IntegrationOptions;
+use crate::build_cache::{self, Explanation};
+use crate::compilation::Platform;
+use crate::compilation_data::{Compilation, CompilationJob, CompilationJobKind, Linking};
+use crate::compilers::{CompileOptions, GeneratedCodes};
+use crate::hash_extra;
 use crate::linkers::LinkOptions;
 use crate::project_data::Project;
-use crate::project_files::{self, ProjectFiles};
+use crate::project_files::{self, IsPathHandled, ProjectFiles};
 use crate::shader_data::ShaderSet;
 use crate::target_code_generators::{self, GenerateTargetCodeOptions, TargetCodeGenerator};
 use crate::{Error, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 use std::time::{Duration, Instant};
 
+pub struct CacheCheckedEvent<'a> {
+	pub explanation: &'a Explanation,
+}
+
 pub struct ExecutableBuiltEvent<'a> {
 	pub path: &'a Path,
+	pub platform: Platform,
 }
 
 impl ExecutableBuiltEvent<'_> {
@@ -27,6 +40,7 @@ impl ExecutableBuiltEvent<'_> {
 
 pub struct LibraryBuiltEvent<'a> {
 	pub path: &'a Path,
+	pub platform: Platform,
 }
 
 pub struct ShaderSetProvidedEvent<'a> {
@@ -37,10 +51,23 @@ pub struct StaticFilesProvidedEvent<'a> {
 	pub paths: &'a Vec<PathBuf>,
 }
 
+pub struct StageFinishedEvent<'a> {
+	pub duration: Duration,
+	pub stage: &'a str,
+	pub successful: bool,
+}
+
+pub struct StageStartedEvent<'a> {
+	pub stage: &'a str,
+}
+
 pub enum BuildEvent<'a> {
+	CacheChecked(CacheCheckedEvent<'a>),
 	ExecutableBuilt(ExecutableBuiltEvent<'a>),
 	LibraryBuilt(LibraryBuiltEvent<'a>),
 	ShaderSetProvided(ShaderSetProvidedEvent<'a>),
+	StageFinished(StageFinishedEvent<'a>),
+	StageStarted(StageStartedEvent<'a>),
 	StaticFilesProvided(StaticFilesProvidedEvent<'a>),
 }
 
@@ -64,15 +91,206 @@ impl FromStr for BuildTarget {
 }
 
 pub struct BuildOptions<'a> {
+	/// Tells in cache explanations which inputs changed since the last build.
+	pub explain: bool,
 	pub force: bool,
+	/// Maximum number of compile jobs running at once, defaults to the available parallelism.
+	pub jobs: Option<usize>,
+	/// Platform to build for, defaults to the project setting or the first one the tools support.
+	pub platform: Option<Platform>,
 	pub project: &'a Project,
 	pub target: BuildTarget,
 }
 
+fn finish_stage(
+	stage: &str,
+	event_listener: &mut dyn FnMut(BuildEvent) -> (),
+	duration: Duration,
+	explanations: Vec<Explanation>,
+	successful: bool,
+) {
+	if successful {
+		build_cache::keep_last_builds(&explanations);
+	}
+
+	for explanation in explanations {
+		event_listener(BuildEvent::CacheChecked(CacheCheckedEvent {
+			explanation: &explanation,
+		}));
+	}
+
+	event_listener(BuildEvent::StageFinished(StageFinishedEvent {
+		duration,
+		stage,
+		successful,
+	}));
+}
+
+// Surrounds a stage with events, cache explanations included.
+fn run_stage<T>(
+	stage: &str,
+	event_listener: &mut dyn FnMut(BuildEvent) -> (),
+	f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+	event_listener(BuildEvent::StageStarted(StageStartedEvent { stage }));
+
+	let start = Instant::now();
+	let result = f();
+	let duration = start.elapsed();
+
+	finish_stage(
+		stage,
+		event_listener,
+		duration,
+		build_cache::take_explanations(),
+		result.is_ok(),
+	);
+
+	result
+}
+
+enum CompileJobMessage {
+	Started(usize),
+	Finished {
+		duration: Duration,
+		explanations: Vec<Explanation>,
+		index: usize,
+		result: Result<Linking>,
+	},
+}
+
+// Compiles jobs in parallel, and returns their linkings in the jobs order.
+fn compile_jobs(
+	jobs: &[(CompilationJob, String)],
+	job_count: usize,
+	compile: &(dyn Fn(&CompilationJob, &mut Linking) -> Result<()> + Sync),
+	event_listener: &mut dyn FnMut(BuildEvent) -> (),
+) -> Result<Vec<Linking>> {
+	let next_index = AtomicUsize::new(0);
+	let failed = AtomicBool::new(false);
+
+	let mut results = jobs.iter().map(|_| None).collect::<Vec<_>>();
+
+	thread::scope(|scope| {
+		let (sender, receiver) = mpsc::channel();
+
+		for _ in 0..job_count.max(1).min(jobs.len()) {
+			let sender = sender.clone();
+			let next_index = &next_index;
+			let failed = &failed;
+			scope.spawn(move || loop {
+				// Once a job has failed, remaining jobs are not started.
+				if failed.load(Ordering::SeqCst) {
+					break;
+				}
+				let index = next_index.fetch_add(1, Ordering::SeqCst);
+				if index >= jobs.len() {
+					break;
+				}
+
+				let _ = sender.send(CompileJobMessage::Started(index));
+
+				let start = Instant::now();
+				let mut linking = Linking::default();
+				let result = compile(&jobs[index].0, &mut linking).map(|_| linking);
+				let duration = start.elapsed();
+
+				if result.is_err() {
+					failed.store(true, Ordering::SeqCst);
+				}
+
+				let _ = sender.send(CompileJobMessage::Finished {
+					duration,
+					explanations: build_cache::take_explanations(),
+					index,
+					result,
+				});
+			});
+		}
+
+		// Only workers hold senders from now on, so that receiving ends with them.
+		drop(sender);
+
+		for message in receiver {
+			match message {
+				CompileJobMessage::Started(index) => {
+					event_listener(BuildEvent::StageStarted(StageStartedEvent {
+						stage: &jobs[index].1,
+					}));
+				}
+				CompileJobMessage::Finished {
+					duration,
+					explanations,
+					index,
+					result,
+				} => {
+					finish_stage(
+						&jobs[index].1,
+						event_listener,
+						duration,
+						explanations,
+						result.is_ok(),
+					);
+					results[index] = Some(result);
+				}
+			}
+		}
+	});
+
+	// Reports the first error in the jobs order, jobs which did not start are skipped.
+	results.into_iter().flatten().collect()
+}
+
+pub fn instantiate_target_code_generator<'a>(
+	project: &'a Project,
+	target: BuildTarget,
+) -> Result<Box<(dyn TargetCodeGenerator + 'a)>> {
+	let instance: Box<(dyn TargetCodeGenerator + 'a)> = match target {
+		BuildTarget::Executable => project
+			.settings
+			.executable_target_code_generator
+			.instantiate(project)?,
+		BuildTarget::Library => {
+			Box::new(target_code_generators::library::LibraryTargetCodeGenerator::new(project)?)
+		}
+	};
+	Ok(instance)
+}
+
+/// Loads the project files, skipping the ones handled by the stages, e.g. the shader provider.
+pub fn load_project_files<'a>(
+	project: &'a Project,
+	handled_paths: Vec<IsPathHandled<'a>>,
+) -> Result<ProjectFiles> {
+	let mut ignore_paths: Vec<IsPathHandled> = vec![Box::new(project_files::is_ignored_path)];
+	ignore_paths.extend(handled_paths);
+
+	ProjectFiles::load(
+		&project.directory,
+		&project_files::LoadOptions {
+			base_directories: &project.settings.base_directories,
+			compiler_paths: &[Box::new(project_files::is_code_path)],
+			exclude: &project.settings.exclude,
+			ignore_paths: &ignore_paths,
+			include: &project.settings.include,
+			sources: &project.settings.sources,
+		},
+	)
+}
+
 pub fn build(
 	options: &BuildOptions,
 	event_listener: &mut dyn FnMut(BuildEvent) -> (),
 ) -> Result<()> {
+	let result = build_stages(options, event_listener);
+
+	// Stages which succeeded are recorded even if a later one failed.
+	build_cache::record_last_builds();
+
+	result
+}
+
+fn build_stages(options: &BuildOptions, event_listener: &mut dyn FnMut(BuildEvent)) -> Result<()> {
 	let audio_synthesizer = options
 		.project
 		.settings
@@ -106,18 +324,21 @@ pub fn build(
 		.shader_provider
 		.instantiate(&options.project)?;
 
-	let target_code_generator: Box<(dyn TargetCodeGenerator)> = match options.target {
-		BuildTarget::Executable => Box::new(
-			target_code_generators::executable::ExecutableTargetCodeGenerator::new(
-				&options.project,
-			)?,
-		),
-		BuildTarget::Library => Box::new(
-			target_code_generators::library::LibraryTargetCodeGenerator::new(&options.project)?,
-		),
-	};
+	let target_code_generator = instantiate_target_code_generator(options.project, options.target)?;
 
-	let mut possible_platforms = linker.get_possible_platforms().clone();
+	let project_files = load_project_files(
+		options.project,
+		vec![
+			audio_synthesizer.get_is_path_handled(),
+			shader_provider.get_is_path_handled(),
+		],
+	)?;
+
+	let mut possible_platforms: BTreeSet<Platform> = linker
+		.get_possible_platforms()
+		.intersection(audio_synthesizer.get_possible_platforms())
+		.cloned()
+		.collect();
 
 	let asm_compiler = if audio_synthesizer.requires_asm_compiler()
 		|| target_code_generator.requires_asm_compiler()
@@ -138,6 +359,7 @@ pub fn build(
 
 	let cpp_compiler = if audio_synthesizer.requires_cpp_compiler()
 		|| target_code_generator.requires_cpp_compiler()
+		|| !project_files.get_source_files().is_empty()
 	{
 		let compiler = options
 			.project
@@ -153,56 +375,67 @@ pub fn build(
 		None
 	};
 
-	let platform = *possible_platforms
-		.iter()
-		.next()
-		.ok_or("No possible platform.")?;
+	let platform = match options.platform.or(options.project.settings.platform) {
+		Some(platform) => {
+			if !possible_platforms.contains(&platform) {
+				return Err(Error::message(format!(
+					"Platform {:?} is not supported by the selected tools.",
+					platform
+				)));
+			}
+			platform
+		}
+		None => *possible_platforms
+			.iter()
+			.next()
+			.ok_or("No possible platform.")?,
+	};
 
-	let project_files = ProjectFiles::load(
-		&options.project.directory,
-		&project_files::LoadOptions {
-			compiler_paths: &[Box::new(|path| {
-				if let Some(extension) = path.extension() {
-					if extension.to_string_lossy() == "cpp" {
-						return true;
-					}
-				}
-				false
-			})],
-			ignore_paths: &[
-				Box::new(|path| {
-					if let Some(file_name) = path.file_name() {
-						let file_name = file_name.to_string_lossy();
-						if file_name.starts_with('.') {
-							return true;
-						}
-						if file_name == "shiba.yml" {
-							return true;
-						}
-					}
-					false
-				}),
-				audio_synthesizer.get_is_path_handled(),
-				shader_provider.get_is_path_handled(),
-			],
-		},
-	)?;
+	// Stages see the platform resolved, e.g. the build cache records last builds per platform.
+	let options = &BuildOptions {
+		platform: Some(platform),
+		..*options
+	};
 
 	event_listener(BuildEvent::StaticFilesProvided(StaticFilesProvidedEvent {
 		paths: project_files.get_static_files(),
 	}));
 
 	let project_codes =
-		project_files.get_compiler_codes(options.project.development, options.target)?;
+		project_files.get_compiler_codes(&options.project.get_template_context(options.target))?;
+	project_files::check_code_slots(project_codes.keys(), target_code_generator.get_code_slots())?;
+
+	let mut compilation = Compilation {
+		include_paths: project_files.get_include_paths(),
+		..Default::default()
+	};
 
-	let mut compilation = Compilation::default();
+	for path in project_files.get_source_files() {
+		compilation.jobs.push(CompilationJob {
+			kind: CompilationJobKind::Cpp,
+			path: path.clone(),
 
-	let audio_codes = audio_synthesizer.integrate(options, &mut compilation)?;
+			include_paths: BTreeSet::new(),
+		});
+	}
 
-	let mut shader_set = shader_provider.provide(options)?;
+	let header_digests = project_files.get_header_digests()?;
+
+	let integration_options = IntegrationOptions {
+		system: target_code_generator.get_system(),
+	};
+	let audio_codes = run_stage("audio-integration", event_listener, || {
+		audio_synthesizer.integrate(options, &integration_options, &mut compilation)
+	})?;
+
+	let mut shader_set = run_stage("shader-provision", event_listener, || {
+		shader_provider.provide(options)
+	})?;
 
 	if let Some(shader_minifier) = shader_minifier {
-		shader_set = shader_minifier.minify(options, &shader_set)?;
+		shader_set = run_stage("shader-minification", event_listener, || {
+			shader_minifier.minify(options, &shader_set)
+		})?;
 	}
 
 	event_listener(BuildEvent::ShaderSetProvided(ShaderSetProvidedEvent {
@@ -215,56 +448,110 @@ pub fn build(
 		project_codes: &project_codes,
 		shader_set: &shader_set,
 	};
-	target_code_generator.generate(options, &generate_options, &mut compilation)?;
+	run_stage("target-code-generation", event_listener, || {
+		target_code_generator.generate(options, &generate_options, &mut compilation)
+	})?;
 
 	let mut linking = Linking {
 		common: compilation.common,
 		..Default::default()
 	};
 
-	for mut compilation_job in compilation.jobs.into_iter() {
-		let mut include_paths = compilation.include_paths.clone();
-		include_paths.append(&mut compilation_job.include_paths);
-
+	let include_paths = compilation.include_paths;
+	let compilation_jobs = compilation
+		.jobs
+		.into_iter()
+		.map(|mut compilation_job| {
+			let mut include_paths = include_paths.clone();
+			include_paths.append(&mut compilation_job.include_paths);
+			compilation_job.include_paths = include_paths;
+
+			// Generated sources, e.g. in the build cache, are named by their file name only.
+			let path = compilation_job
+				.path
+				.strip_prefix(&options.project.directory)
+				.unwrap_or_else(|_| {
+					Path::new(compilation_job.path.file_name().unwrap_or_default())
+				});
+			let stage = format!("compilation/{}", path.to_string_lossy());
+			(compilation_job, stage)
+		})
+		.collect::<Vec<_>>();
+
+	let job_count = options.jobs.unwrap_or_else(|| {
+		thread::available_parallelism()
+			.map(|count| count.get())
+			.unwrap_or(1)
+	});
+
+	let compile = |compilation_job: &CompilationJob, linking: &mut Linking| {
+		// Project sources are keyed without the generated codes, so that editing a shader does not recompile them.
+		let generated_codes = if project_files
+			.get_source_files()
+			.contains(&compilation_job.path)
+		{
+			None
+		} else {
+			Some(GeneratedCodes {
+				audio_codes: &audio_codes,
+				project_codes: &project_codes,
+				shader_set: &shader_set,
+			})
+		};
 		let compile_options = CompileOptions {
-			audio_codes: &audio_codes,
-			include_paths: &include_paths,
+			generated_codes,
+			header_digests: &header_digests,
+			include_paths: &compilation_job.include_paths,
 			path: &compilation_job.path,
 			platform,
-			project_codes: &project_codes,
-			shader_set: &shader_set,
 		};
 
-		match compilation_job.kind {
-			CompilationJobKind::Asm => {
-				asm_compiler
-					.as_ref()
-					.unwrap()
-					.compile(options, &compile_options, &mut linking)?
-			}
-			CompilationJobKind::Cpp => {
-				cpp_compiler
-					.as_ref()
-					.unwrap()
-					.compile(options, &compile_options, &mut linking)?
-			}
+		let compiler = match compilation_job.kind {
+			CompilationJobKind::Asm => asm_compiler.as_ref().unwrap(),
+			CompilationJobKind::Cpp => cpp_compiler.as_ref().unwrap(),
 		};
+		compiler.compile(options, &compile_options, linking)
+	};
+
+	for mut job_linking in compile_jobs(&compilation_jobs, job_count, &compile, event_listener)? {
+		linking.sources.append(&mut job_linking.sources);
+	}
+
+	for path in
+		project_files.get_prebuilt_files(platform, &options.project.settings.prebuilt_platforms)?
+	{
+		let contents = fs::read(path).map_err(|err| Error::failed_to_read(path, err))?;
+		linking
+			.prebuilt_digests
+			.insert(path.to_path_buf(), hash_extra::compute_digest(&contents));
+
+		if project_files::is_library_path(path) {
+			linking.common.link_dependencies.insert(path.to_path_buf());
+		} else {
+			linking.sources.push(path.to_path_buf());
+		}
 	}
 
 	let link_options = LinkOptions {
 		linking: &linking,
 		platform,
 	};
-	let path = linker.link(&options, &link_options)?;
+	let path = run_stage("linking", event_listener, || {
+		linker.link(&options, &link_options)
+	})?;
 
 	match options.target {
 		BuildTarget::Executable => {
 			event_listener(BuildEvent::ExecutableBuilt(ExecutableBuiltEvent {
 				path: &path,
+				platform,
 			}));
 		}
 		BuildTarget::Library => {
-			event_listener(BuildEvent::LibraryBuilt(LibraryBuiltEvent { path: &path }));
+			event_listener(BuildEvent::LibraryBuilt(LibraryBuiltEvent {
+				path: &path,
+				platform,
+			}));
 		}
 	};
 
@@ -0,0 +1,616 @@
+use crate::build::BuildOptions;
+use crate::hash_extra;
+use crate::paths::{BUILD_CACHE_DIRECTORY, BUILD_ROOT_DIRECTORY, LAST_BUILDS_DIRECTORY};
+use crate::{Error, Result};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Bump when the canonical serialization changes, so that old entries are not reused.
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+// Stages render templates embedded in the executable, which may change with each version.
+const SHIBA_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const LOCK_FILENAME: &str = ".lock";
+
+pub const MANIFEST_FILENAME: &str = "manifest.json";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+	pub digest: String,
+	pub fields: BTreeMap<String, String>,
+	pub format_version: u32,
+	#[serde(default)]
+	pub last_used: u64,
+	#[serde(default)]
+	pub projects: BTreeSet<PathBuf>,
+	#[serde(default)]
+	pub stage: String,
+}
+
+// serde_json objects are sorted maps, so the string representation of a value is canonical.
+fn to_canonical_value(value: &impl Serialize) -> Value {
+	serde_json::to_value(value).expect("Failed to serialize inputs.")
+}
+
+// Paths in the project, or in the build root, are made relative to it, so that moving the project or the temporary directory keeps its entries.
+fn make_paths_relative(value: Value, directories: &[&Path]) -> Value {
+	let make_path_relative = |string: String| {
+		for directory in directories {
+			// Most strings are not paths, e.g. codes, so they are skipped early.
+			if !string.starts_with(&*directory.to_string_lossy()) {
+				continue;
+			}
+			if let Ok(relative_path) = Path::new(&string).strip_prefix(directory) {
+				return relative_path.to_string_lossy().to_string();
+			}
+		}
+		string
+	};
+
+	match value {
+		Value::String(string) => Value::String(make_path_relative(string)),
+		Value::Array(values) => Value::Array(
+			values
+				.into_iter()
+				.map(|value| make_paths_relative(value, directories))
+				.collect(),
+		),
+		Value::Object(map) => Value::Object(
+			map.into_iter()
+				.map(|(name, value)| {
+					(
+						make_path_relative(name),
+						make_paths_relative(value, directories),
+					)
+				})
+				.collect(),
+		),
+		value => value,
+	}
+}
+
+// Nested fields are digested too, so that explanations can tell e.g. "options.shader_set".
+const FIELD_DEPTH: usize = 2;
+
+fn collect_field_digests(
+	prefix: &str,
+	value: &Value,
+	depth: usize,
+	fields: &mut BTreeMap<String, String>,
+) {
+	if depth == 0 {
+		return;
+	}
+
+	if let Value::Object(map) = value {
+		for (name, field) in map {
+			let name = format!("{}{}", prefix, name);
+			fields.insert(
+				name.clone(),
+				hash_extra::compute_digest(field.to_string().as_bytes()),
+			);
+			collect_field_digests(&format!("{}.", name), field, depth - 1, fields);
+		}
+	}
+}
+
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0)
+}
+
+pub fn get_project_key(directory: &Path) -> PathBuf {
+	fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf())
+}
+
+fn read_manifest(path: &Path) -> Option<Manifest> {
+	let json = fs::read_to_string(path).ok()?;
+	serde_json::from_str(&json).ok()
+}
+
+fn write_manifest(path: &Path, manifest: &Manifest) -> Result<()> {
+	let json = serde_json::to_string_pretty(manifest).expect("Failed to dump JSON.");
+	write_atomically(path, json)
+}
+
+/// Describes whether a stage reused a cache entry, and why not.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Explanation {
+	/// Inputs fields which differ from the last build of the same output, none if there was no such build, or if explanations were not requested.
+	pub changed_fields: Option<Vec<String>>,
+	pub digest: String,
+	pub forced: bool,
+	pub hit: bool,
+	#[serde(skip)]
+	last_build: PendingLastBuild,
+	pub output: String,
+	pub stage: String,
+}
+
+// Explanations are kept per thread, so that compile jobs running in parallel do not mix them.
+thread_local! {
+	static EXPLANATIONS: RefCell<Vec<Explanation>> = const { RefCell::new(Vec::new()) };
+}
+
+// Last builds of the stages which succeeded, recorded at the end of the build. Stages report on the build thread.
+thread_local! {
+	static SUCCEEDED_LAST_BUILDS: RefCell<Vec<PendingLastBuild>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns the explanations recorded by the current thread since the last call.
+pub fn take_explanations() -> Vec<Explanation> {
+	EXPLANATIONS.with(|explanations| explanations.replace(vec![]))
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct LastBuild {
+	digest: String,
+	fields: BTreeMap<String, String>,
+}
+
+// Last builds are recorded per project, target, profile and platform, by stage and source.
+type LastBuilds = BTreeMap<String, LastBuild>;
+
+#[derive(Clone, Debug)]
+struct PendingLastBuild {
+	key: String,
+	last_build: LastBuild,
+	path: PathBuf,
+}
+
+fn read_last_builds(path: &Path) -> LastBuilds {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|json| serde_json::from_str(&json).ok())
+		.unwrap_or_default()
+}
+
+/// Keeps the last builds of a stage which succeeded, until they are recorded.
+pub fn keep_last_builds(explanations: &[Explanation]) {
+	SUCCEEDED_LAST_BUILDS.with(|last_builds| {
+		last_builds.borrow_mut().extend(
+			explanations
+				.iter()
+				.map(|explanation| explanation.last_build.clone()),
+		)
+	});
+}
+
+/// Records the last builds kept since the last call, so that the next build can explain what changed.
+pub fn record_last_builds() {
+	let mut pending_last_builds_by_path = BTreeMap::new();
+	for pending_last_build in SUCCEEDED_LAST_BUILDS.with(|last_builds| last_builds.replace(vec![]))
+	{
+		pending_last_builds_by_path
+			.entry(pending_last_build.path.clone())
+			.or_insert_with(Vec::new)
+			.push(pending_last_build);
+	}
+
+	for (path, pending_last_builds) in pending_last_builds_by_path {
+		let mut last_builds = read_last_builds(&path);
+		for pending_last_build in pending_last_builds {
+			last_builds.insert(pending_last_build.key, pending_last_build.last_build);
+		}
+
+		// The record is only informative, failing to update it does not fail the build.
+		let json = serde_json::to_string_pretty(&last_builds).expect("Failed to dump JSON.");
+		let _ = write_atomically(&path, json);
+	}
+}
+
+/// Directory where a stage produces an entry before publishing it, removed once dropped.
+pub struct ScratchDirectory {
+	path: PathBuf,
+}
+
+impl Deref for ScratchDirectory {
+	type Target = Path;
+
+	fn deref(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl AsRef<Path> for ScratchDirectory {
+	fn as_ref(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for ScratchDirectory {
+	fn drop(&mut self) {
+		// The entry is already published, or failed anyway.
+		let _ = fs::remove_dir_all(&self.path);
+	}
+}
+
+/// A locked cache entry. Other builds wait for the lock to be released before using the same entry.
+pub struct LockedEntry {
+	digest: String,
+	directory: PathBuf,
+	explain: bool,
+	fields: BTreeMap<String, String>,
+	force: bool,
+	last_builds_path: PathBuf,
+	project_directory: PathBuf,
+	stage: String,
+	_lock_file: File,
+}
+
+impl LockedEntry {
+	pub fn get_directory(&self) -> &Path {
+		&self.directory
+	}
+
+	/// Creates an empty directory dedicated to producing this entry, removed once dropped.
+	pub fn create_scratch_directory(&self) -> Result<ScratchDirectory> {
+		let path = BUILD_ROOT_DIRECTORY.join(&self.stage).join(&self.digest);
+		if path.exists() {
+			fs::remove_dir_all(&path)
+				.map_err(|err| Error::failed_to_remove_directory(&path, err))?;
+		}
+		fs::create_dir_all(&path).map_err(|err| Error::failed_to_create_directory(&path, err))?;
+		Ok(ScratchDirectory { path })
+	}
+
+	/// Tells whether the output is already in the entry, and records the explanation.
+	pub fn is_hit(&self, output_path: &Path) -> bool {
+		self.is_hit_for_source(output_path, output_path)
+	}
+
+	/// Same as is_hit, for stages producing an output per source, e.g. compilers.
+	pub fn is_hit_for_source(&self, output_path: &Path, source_path: &Path) -> bool {
+		let hit = !self.force && output_path.exists();
+
+		let output = output_path
+			.file_name()
+			.unwrap_or_default()
+			.to_string_lossy()
+			.to_string();
+
+		// Sources generated in the build cache are identified by their name only.
+		let source = match source_path.strip_prefix(&self.project_directory) {
+			Ok(relative_path) => relative_path.to_string_lossy().to_string(),
+			Err(_) => source_path
+				.file_name()
+				.unwrap_or_default()
+				.to_string_lossy()
+				.to_string(),
+		};
+		let key = format!("{}/{}", self.stage, source);
+
+		// Last builds are only read to explain, they are written at the end of the build.
+		let last_build = if self.explain {
+			read_last_builds(&self.last_builds_path).remove(&key)
+		} else {
+			None
+		};
+
+		let changed_fields = last_build.map(|last_build| {
+			let changed_fields = self
+				.fields
+				.keys()
+				.chain(last_build.fields.keys())
+				.collect::<BTreeSet<_>>()
+				.into_iter()
+				.filter(|&name| self.fields.get(name) != last_build.fields.get(name))
+				.collect::<Vec<_>>();
+
+			// Only the most nested changes are relevant.
+			changed_fields
+				.iter()
+				.filter(|&name| {
+					let prefix = format!("{}.", name);
+					!changed_fields
+						.iter()
+						.any(|other| other.starts_with(&prefix))
+				})
+				.map(|&name| name.clone())
+				.collect()
+		});
+
+		EXPLANATIONS.with(|explanations| {
+			explanations.borrow_mut().push(Explanation {
+				changed_fields,
+				digest: self.digest.clone(),
+				forced: self.force,
+				hit,
+				last_build: PendingLastBuild {
+					key,
+					last_build: LastBuild {
+						digest: self.digest.clone(),
+						fields: self.fields.clone(),
+					},
+					path: self.last_builds_path.clone(),
+				},
+				output,
+				stage: self.stage.clone(),
+			})
+		});
+
+		hit
+	}
+}
+
+/// Locks the cache entry for the given inputs, creating it if needed.
+///
+/// The stage identifies the tool producing the entry, e.g. "cpp-compilers/gcc".
+pub fn lock_entry(
+	build_options: &BuildOptions,
+	stage: &str,
+	value: &impl Serialize,
+) -> Result<LockedEntry> {
+	let mut value = make_paths_relative(
+		to_canonical_value(value),
+		&[&build_options.project.directory, &BUILD_ROOT_DIRECTORY],
+	);
+
+	// The selected profile is part of the inputs.
+	if let (Some(profile), Value::Object(map)) = (&build_options.project.profile, &mut value) {
+		map.insert("profile".to_string(), Value::from(profile.as_str()));
+	}
+
+	let digest = hash_extra::compute_digest(
+		to_canonical_value(&(CACHE_FORMAT_VERSION, SHIBA_VERSION, &value))
+			.to_string()
+			.as_bytes(),
+	);
+
+	let directory = BUILD_CACHE_DIRECTORY.join(&digest);
+	fs::create_dir_all(&directory)
+		.map_err(|err| Error::failed_to_create_directory(&directory, err))?;
+
+	let lock_path = directory.join(LOCK_FILENAME);
+	let lock_file = OpenOptions::new()
+		.create(true)
+		.truncate(false)
+		.write(true)
+		.open(&lock_path)
+		.map_err(|err| Error::failed_to_write(&lock_path, err))?;
+	lock_file
+		.lock_exclusive()
+		.map_err(|err| Error::failed_to_lock(&lock_path, err))?;
+
+	let manifest_path = directory.join(MANIFEST_FILENAME);
+	let mut manifest = read_manifest(&manifest_path).unwrap_or_else(|| {
+		let mut fields = BTreeMap::new();
+		collect_field_digests("", &value, FIELD_DEPTH, &mut fields);
+
+		Manifest {
+			digest: digest.clone(),
+			fields,
+			format_version: CACHE_FORMAT_VERSION,
+			stage: stage.to_string(),
+			..Default::default()
+		}
+	});
+	let project_key = get_project_key(&build_options.project.directory);
+
+	manifest.last_used = now();
+	manifest.projects.insert(project_key.clone());
+	write_manifest(&manifest_path, &manifest)?;
+
+	let last_builds_path = LAST_BUILDS_DIRECTORY.join(format!(
+		"{}.json",
+		hash_extra::compute_digest(
+			to_canonical_value(&(
+				&project_key,
+				build_options.target,
+				&build_options.project.profile,
+				build_options.platform
+			))
+			.to_string()
+			.as_bytes()
+		)
+	));
+
+	Ok(LockedEntry {
+		digest,
+		directory,
+		explain: build_options.explain,
+		fields: manifest.fields,
+		force: build_options.force,
+		last_builds_path,
+		project_directory: build_options.project.directory.clone(),
+		stage: stage.to_string(),
+		_lock_file: lock_file,
+	})
+}
+
+fn get_temporary_path(path: &Path) -> PathBuf {
+	let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+	file_name.push(format!(".{}.tmp", process::id()));
+	path.with_file_name(file_name)
+}
+
+/// Writes to a temporary file first, so that readers never see partial contents.
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+	let temporary_path = get_temporary_path(path);
+	fs::write(&temporary_path, contents)
+		.map_err(|err| Error::failed_to_write(&temporary_path, err))?;
+	fs::rename(&temporary_path, path)
+		.map_err(|err| Error::failed_to_rename(&temporary_path, path, err))
+}
+
+/// Copies to a temporary file first, so that readers never see partial contents.
+pub fn copy_atomically(from: &Path, to: &Path) -> Result<()> {
+	let temporary_path = get_temporary_path(to);
+	fs::copy(from, &temporary_path)
+		.map_err(|err| Error::failed_to_copy(from, &temporary_path, err))?;
+	fs::rename(&temporary_path, to).map_err(|err| Error::failed_to_rename(&temporary_path, to, err))
+}
+
+pub struct Entry {
+	pub manifest: Manifest,
+	pub path: PathBuf,
+	pub size: u64,
+}
+
+impl Entry {
+	pub fn get_age(&self) -> Duration {
+		Duration::from_secs(now().saturating_sub(self.manifest.last_used))
+	}
+}
+
+fn get_directory_size(path: &Path) -> u64 {
+	fs::read_dir(path)
+		.map(|entries| {
+			entries
+				.filter_map(|entry| entry.ok())
+				.map(|entry| match entry.file_type() {
+					Ok(file_type) if file_type.is_dir() => get_directory_size(&entry.path()),
+					_ => entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+				})
+				.sum()
+		})
+		.unwrap_or(0)
+}
+
+/// Lists cache entries, least recently used first.
+pub fn list_entries() -> Result<Vec<Entry>> {
+	let read_dir = fs::read_dir(&*BUILD_CACHE_DIRECTORY)
+		.map_err(|err| Error::failed_to_read_directory(&*BUILD_CACHE_DIRECTORY, err))?;
+
+	let mut entries = read_dir
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.filter(|path| path.is_dir())
+		.map(|path| {
+			// Entries created by older versions have no manifest.
+			let manifest = read_manifest(&path.join(MANIFEST_FILENAME)).unwrap_or_else(|| {
+				let last_used = fs::metadata(&path)
+					.and_then(|metadata| metadata.modified())
+					.ok()
+					.and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+					.map(|duration| duration.as_secs())
+					.unwrap_or(0);
+				Manifest {
+					last_used,
+					..Default::default()
+				}
+			});
+			let size = get_directory_size(&path);
+			Entry {
+				manifest,
+				path,
+				size,
+			}
+		})
+		.collect::<Vec<_>>();
+
+	entries.sort_by_key(|entry| entry.manifest.last_used);
+
+	Ok(entries)
+}
+
+pub struct PruneOptions {
+	pub max_age: Option<Duration>,
+	pub max_size: Option<u64>,
+}
+
+/// Removes entries older than the max age, then least recently used entries until the cache fits in the max size.
+pub fn prune(options: &PruneOptions) -> Result<Vec<Entry>> {
+	let entries = list_entries()?;
+
+	let mut total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+
+	let mut removed_entries = vec![];
+	for entry in entries {
+		let too_old = matches!(options.max_age, Some(max_age) if entry.get_age() > max_age);
+		let too_big = matches!(options.max_size, Some(max_size) if total_size > max_size);
+		if !too_old && !too_big {
+			continue;
+		}
+
+		// Entries being used by a build are kept.
+		let lock_path = entry.path.join(LOCK_FILENAME);
+		if let Ok(lock_file) = File::open(&lock_path) {
+			if lock_file.try_lock_exclusive().is_err() {
+				continue;
+			}
+
+			// Windows cannot remove a directory while a file in it is open.
+			let _ = lock_file.unlock();
+			drop(lock_file);
+		}
+
+		fs::remove_dir_all(&entry.path)
+			.map_err(|err| Error::failed_to_remove_directory(&entry.path, err))?;
+		total_size -= entry.size;
+		removed_entries.push(entry);
+	}
+
+	Ok(removed_entries)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	#[test]
+	fn test_canonical_value() {
+		let mut a = HashMap::new();
+		a.insert("b", 1);
+		a.insert("a", 2);
+
+		let mut b = BTreeMap::new();
+		b.insert("a", 2);
+		b.insert("b", 1);
+
+		assert_eq!(
+			to_canonical_value(&a).to_string(),
+			to_canonical_value(&b).to_string()
+		);
+		assert_eq!(to_canonical_value(&a).to_string(), r#"{"a":2,"b":1}"#);
+	}
+
+	#[test]
+	fn test_collect_field_digests() {
+		let value = serde_json::json!({
+			"a": {"b": {"c": 1}},
+			"d": 2,
+		});
+
+		let mut fields = BTreeMap::new();
+		collect_field_digests("", &value, 2, &mut fields);
+
+		assert_eq!(fields.keys().collect::<Vec<_>>(), vec!["a", "a.b", "d"]);
+	}
+
+	#[test]
+	fn test_make_paths_relative() {
+		let directories = [Path::new("/projects/demo"), Path::new("/tmp/shiba/build")];
+		let value = serde_json::json!({
+			"include-paths": ["/projects/demo/include", "/usr/include"],
+			"header-digests": {"/projects/demo/a.h": "digest"},
+			"object-paths": ["/tmp/shiba/build/compilation/main.obj"],
+			"path": "/projects/demo-other/main.cpp",
+		});
+
+		assert_eq!(
+			make_paths_relative(value, &directories),
+			serde_json::json!({
+				"include-paths": [Path::new("include").to_string_lossy(), "/usr/include"],
+				"header-digests": {"a.h": "digest"},
+				"object-paths": [Path::new("compilation/main.obj").to_string_lossy()],
+				"path": "/projects/demo-other/main.cpp",
+			})
+		);
+	}
+}
@@ -1,46 +1,201 @@
 use crate::build::{self, BuildEvent, BuildOptions, BuildTarget};
+use crate::compilation::Platform;
 use crate::project_data::Project;
-use crate::Result;
-use std::path::Path;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tera::{Context, Tera};
 
 pub struct Options<'a> {
+	pub explain: bool,
 	pub force: bool,
+	pub jobs: Option<usize>,
+	pub platforms: &'a [Platform],
+	pub profile: Option<&'a str>,
 	pub project_directory: &'a Path,
 	pub target: BuildTarget,
+	/// Overrides of the template variables.
+	pub variables: &'a [(String, serde_json::Value)],
+	/// Where to write the timings report, as HTML if the extension is .html, as JSON otherwise.
+	pub timings: Option<&'a Path>,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct StageTimings {
+	duration: f32,
+	stage: String,
+	start: f32,
+	successful: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct BuildTimings {
+	duration: f32,
+	platform: Option<Platform>,
+	stages: Vec<StageTimings>,
+	successful: bool,
+	target: BuildTarget,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TimingsReport {
+	builds: Vec<BuildTimings>,
+}
+
+fn write_timings_report(path: &Path, report: &TimingsReport) -> Result<()> {
+	let is_html = matches!(path.extension(), Some(extension) if extension == "html");
+
+	let contents = if is_html {
+		let mut tera = Tera::default();
+		tera.add_raw_template("timings", include_str!("build_timings.html.tera"))
+			.expect("Failed to add template.");
+		tera.render(
+			"timings",
+			&Context::from_serialize(report).expect("Failed to create context."),
+		)
+		.map_err(|err| Error::failed_to_render_template("timings", err))?
+	} else {
+		serde_json::to_string_pretty(report).expect("Failed to dump JSON.")
+	};
+
+	fs::write(path, contents).map_err(|err| Error::failed_to_write(path, err))
+}
+
+// Each platform is built with its own settings, see the platforms section.
+fn load_project(options: &Options, platform: Option<Platform>) -> Result<Project> {
+	let mut project = Project::load(
+		options.project_directory,
+		options.target,
+		options.profile,
+		platform,
+		options.tool_overrides,
+	)?;
+	project
+		.settings
+		.variables
+		.extend(options.variables.iter().cloned());
+	Ok(project)
 }
 
 pub fn execute(options: &Options) -> Result<()> {
-	let project = Project::load(options.project_directory, options.target)?;
+	let build_start = Cell::new(Instant::now());
+	let built_platform = Cell::new(None);
+	let stage_timings = RefCell::new(vec![]);
 
 	let mut event_listener = |event: BuildEvent| match event {
-		BuildEvent::ExecutableBuilt(event) => match event.get_size() {
-			Ok(size) => {
-				println!("Executable compiled:");
-				println!("  Path: {:?}", event.path);
-				println!("  Size: {}", size);
-			}
-			Err(_err) => {
-				println!("Unexpected error while getting size.");
+		BuildEvent::CacheChecked(event) if options.explain => {
+			let explanation = event.explanation;
+			let reason = if explanation.hit {
+				"hit".to_string()
+			} else if explanation.forced {
+				"miss, forced".to_string()
+			} else {
+				match &explanation.changed_fields {
+					None => "miss, no previous build".to_string(),
+					Some(fields) if fields.is_empty() => "miss, output missing".to_string(),
+					Some(fields) => format!("miss, {} changed", fields.join(", ")),
+				}
+			};
+			println!(
+				"{} {}: {} ({})",
+				explanation.stage, explanation.output, reason, explanation.digest
+			);
+		}
+
+		BuildEvent::ExecutableBuilt(event) => {
+			built_platform.set(Some(event.platform));
+			match event.get_size() {
+				Ok(size) => {
+					println!("Executable compiled:");
+					println!("  Path: {:?}", event.path);
+					println!("  Platform: {:?}", event.platform);
+					println!("  Size: {}", size);
+				}
+				Err(_err) => {
+					println!("Unexpected error while getting size.");
+				}
 			}
-		},
+		}
 
 		BuildEvent::LibraryBuilt(event) => {
+			built_platform.set(Some(event.platform));
 			println!("Library compiled:");
 			println!("  Path: {:?}", event.path);
+			println!("  Platform: {:?}", event.platform);
+		}
+
+		BuildEvent::StageFinished(event) => {
+			// Compile jobs run in parallel, so the start is derived from the stage own duration.
+			let start = build_start.get().elapsed().saturating_sub(event.duration);
+			stage_timings.borrow_mut().push(StageTimings {
+				duration: event.duration.as_secs_f32(),
+				stage: event.stage.to_string(),
+				start: start.as_secs_f32(),
+				successful: event.successful,
+			});
 		}
 
 		_ => {}
 	};
 
-	let duration = build::build_duration(
-		&BuildOptions {
-			force: options.force,
-			project: &project,
+	// Without explicit platforms, the settings or the tools decide.
+	let platforms = if options.platforms.is_empty() {
+		vec![None]
+	} else {
+		options.platforms.iter().cloned().map(Some).collect()
+	};
+
+	let mut report = TimingsReport { builds: vec![] };
+
+	let mut result = Ok(());
+	for platform in platforms {
+		build_start.set(Instant::now());
+		built_platform.set(platform);
+
+		result = load_project(options, platform).and_then(|project| {
+			build::build(
+				&BuildOptions {
+					explain: options.explain,
+					force: options.force,
+					jobs: options.jobs,
+					platform,
+					project: &project,
+					target: options.target,
+				},
+				&mut event_listener,
+			)
+		});
+
+		let duration = build_start.get().elapsed();
+
+		report.builds.push(BuildTimings {
+			duration: duration.as_secs_f32(),
+			platform: built_platform.get(),
+			stages: stage_timings.replace(vec![]),
+			successful: result.is_ok(),
 			target: options.target,
-		},
-		&mut event_listener,
-	)?;
+		});
+
+		if result.is_err() {
+			break;
+		}
+
+		println!("Build duration: {:?}.", duration);
+	}
+
+	// The report is also written for failed builds, up to the failing stage.
+	if let Some(path) = options.timings {
+		write_timings_report(path, &report)?;
+		println!("Timings report written to {:?}.", path);
+	}
 
-	println!("Build duration: {:?}.", duration);
-	Ok(())
+	result
 }
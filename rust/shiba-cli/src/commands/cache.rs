@@ -0,0 +1,118 @@
+use crate::build_cache::{self, Entry, PruneOptions as BuildCachePruneOptions};
+use crate::{Error, Result};
+use std::path::Path;
+use std::time::Duration;
+
+fn format_age(age: Duration) -> String {
+	let seconds = age.as_secs();
+	if seconds < 60 * 60 {
+		format!("{}m", seconds / 60)
+	} else if seconds < 24 * 60 * 60 {
+		format!("{}h", seconds / (60 * 60))
+	} else {
+		format!("{}d", seconds / (24 * 60 * 60))
+	}
+}
+
+fn format_size(size: u64) -> String {
+	if size < 1024 {
+		format!("{} B", size)
+	} else if size < 1024 * 1024 {
+		format!("{:.1} KiB", size as f64 / 1024.0)
+	} else {
+		format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0))
+	}
+}
+
+fn print_entry(entry: &Entry) {
+	let name = entry
+		.path
+		.file_name()
+		.map(|name| name.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let stage = if entry.manifest.stage.is_empty() {
+		"unknown"
+	} else {
+		&entry.manifest.stage
+	};
+	println!(
+		"{:16}  {:40}  {:>10}  {:>5}",
+		&name[..16.min(name.len())],
+		stage,
+		format_size(entry.size),
+		format_age(entry.get_age()),
+	);
+}
+
+pub struct ListOptions<'a> {
+	pub current_project: bool,
+	pub project_directory: &'a Path,
+}
+
+pub fn list(options: &ListOptions) -> Result<()> {
+	let mut entries = build_cache::list_entries()?;
+
+	if options.current_project {
+		let project_key = build_cache::get_project_key(options.project_directory);
+		entries.retain(|entry| entry.manifest.projects.contains(&project_key));
+	}
+
+	println!(
+		"{:16}  {:40}  {:>10}  {:>5}",
+		"Entry", "Stage", "Size", "Used"
+	);
+	for entry in &entries {
+		print_entry(entry);
+	}
+
+	let total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+	println!(
+		"{} entries, {} total.",
+		entries.len(),
+		format_size(total_size)
+	);
+	Ok(())
+}
+
+pub struct PruneOptions {
+	pub max_age_days: Option<f32>,
+	pub max_size_mib: Option<f32>,
+}
+
+fn get_max_age(days: f32) -> Result<Duration> {
+	Duration::try_from_secs_f32(days * 24. * 60. * 60.).map_err(|_| {
+		Error::message(format!(
+			"Invalid max age {}, expected a positive number of days.",
+			days
+		))
+	})
+}
+
+fn get_max_size(mib: f32) -> Result<u64> {
+	if !mib.is_finite() || mib < 0. {
+		return Err(Error::message(format!(
+			"Invalid max size {}, expected a positive number of MiB.",
+			mib
+		)));
+	}
+	Ok((mib as f64 * 1024. * 1024.) as u64)
+}
+
+pub fn prune(options: &PruneOptions) -> Result<()> {
+	let removed_entries = build_cache::prune(&BuildCachePruneOptions {
+		max_age: options.max_age_days.map(get_max_age).transpose()?,
+		max_size: options.max_size_mib.map(get_max_size).transpose()?,
+	})?;
+
+	for entry in &removed_entries {
+		print_entry(entry);
+	}
+
+	let total_size: u64 = removed_entries.iter().map(|entry| entry.size).sum();
+	println!(
+		"{} entries removed, {} freed.",
+		removed_entries.len(),
+		format_size(total_size)
+	);
+	Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::configuration::Configuration;
+use crate::Result;
+use std::path::{Path, PathBuf};
+
+pub struct ListOptions<'a> {
+	pub project_directory: &'a Path,
+	pub tool_overrides: &'a [(String, PathBuf)],
+}
+
+/// Lists the effective tool paths, and where they come from.
+pub fn list(options: &ListOptions) -> Result<()> {
+	let configuration = Configuration::load(options.project_directory, options.tool_overrides)?;
+
+	let names = configuration.get_names();
+	let name_width = names
+		.iter()
+		.map(|name| name.len())
+		.max()
+		.unwrap_or_default();
+	for name in names {
+		let configured_path = configuration.get_configured_path(name);
+		println!(
+			"{:width$}  {}  ({})",
+			name,
+			configured_path.path.to_string_lossy(),
+			configured_path.source,
+			width = name_width
+		);
+	}
+
+	Ok(())
+}
@@ -0,0 +1,258 @@
+use crate::build::{self, BuildTarget};
+use crate::compilation::CompilationJobEmitter;
+use crate::configuration::{self, Configuration};
+use crate::hash_extra;
+use crate::paths::USER_SETTINGS_DIRECTORY;
+use crate::project_data::Project;
+use crate::{Error, Result};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+pub struct Options<'a> {
+	pub profile: Option<&'a str>,
+	pub project_directory: &'a Path,
+	pub target: BuildTarget,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
+}
+
+struct Doctor<'a> {
+	configuration: &'a Configuration,
+	/// Set while checking tools needed by some commands only, whose problems do not fail the check.
+	is_optional: Cell<bool>,
+	problem_count: Cell<usize>,
+}
+
+impl<'a> Doctor<'a> {
+	fn report_problem(&self, message: String) {
+		if self.is_optional.get() {
+			println!("  [optional] {}", message);
+		} else {
+			self.problem_count.set(self.problem_count.get() + 1);
+			println!("  [problem] {}", message);
+		}
+	}
+
+	fn check_instance<T>(&self, stage: &str, instance: Result<T>) -> Option<T> {
+		println!("{}", stage);
+		match instance {
+			Ok(instance) => Some(instance),
+			Err(err) => {
+				self.report_problem(format!("Failed to instantiate: {}", err));
+				None
+			}
+		}
+	}
+
+	fn get_hint(&self, name: &str) -> String {
+		let mut ways = vec![
+			format!(
+				"paths.{} in {} or .shiba/config.yml",
+				name,
+				USER_SETTINGS_DIRECTORY.join("config.yml").to_string_lossy()
+			),
+			format!("--tool {}=<path>", name),
+		];
+		if let Some(variable) = configuration::get_environment_variable_name(name) {
+			ways.insert(1, variable);
+		}
+		format!("Set {}.", ways.join(", or "))
+	}
+
+	fn check_directory(&self, name: &str, required_file: &Path) {
+		let configured_path = self.configuration.get_configured_path(name);
+		let file_path = configured_path.path.join(required_file);
+		if file_path.is_file() {
+			println!(
+				"  {}: {} ({})",
+				name,
+				configured_path.path.to_string_lossy(),
+				configured_path.source
+			);
+		} else {
+			self.report_problem(format!(
+				"{}: cannot find {}. {}",
+				name,
+				file_path.to_string_lossy(),
+				self.get_hint(name)
+			));
+		}
+	}
+
+	fn check_executable(&self, name: &str, version_args: &[&str]) {
+		let configured_path = self.configuration.get_configured_path(name);
+		let executable_path = match hash_extra::find_executable(&configured_path.path) {
+			Some(executable_path) => executable_path,
+			None => {
+				self.report_problem(format!(
+					"{}: cannot find {}. {}",
+					name,
+					configured_path.path.to_string_lossy(),
+					self.get_hint(name)
+				));
+				return;
+			}
+		};
+
+		let output = match Command::new(&executable_path)
+			.args(version_args)
+			.stdin(Stdio::null())
+			.output()
+		{
+			Ok(output) => output,
+			Err(err) => {
+				self.report_problem(format!(
+					"{}: failed to execute {}: {}. {}",
+					name,
+					executable_path.to_string_lossy(),
+					err,
+					self.get_hint(name)
+				));
+				return;
+			}
+		};
+
+		// Some tools print their version on stderr.
+		let version = [&output.stdout, &output.stderr]
+			.iter()
+			.flat_map(|output| {
+				String::from_utf8_lossy(output)
+					.lines()
+					.map(str::to_string)
+					.collect::<Vec<_>>()
+			})
+			.map(|line| line.trim().to_string())
+			.find(|line| !line.is_empty())
+			.unwrap_or_else(|| "unknown version".to_string());
+
+		println!(
+			"  {}: {} ({}): {}",
+			name,
+			executable_path.to_string_lossy(),
+			configured_path.source,
+			version
+		);
+	}
+
+	fn check_tool(&self, name: &str) {
+		match name {
+			"7z" | "crinkler" => self.check_executable(name, &[]),
+			"glew" => self.check_directory(name, &Path::new("include").join("GL").join("glew.h")),
+			"nasm" => self.check_executable(name, &["-v"]),
+			"oidos" => self.check_directory(name, &Path::new("convert").join("OidosConvert.py")),
+			"shader-minifier" => self.check_executable(name, &["--help"]),
+			_ => self.check_executable(name, &["--version"]),
+		}
+	}
+
+	fn check_tools(&self, names: &[&str]) {
+		for name in names {
+			self.check_tool(name);
+		}
+	}
+
+	fn check_optional_tool(&self, name: &str) {
+		self.is_optional.set(true);
+		self.check_tool(name);
+		self.is_optional.set(false);
+	}
+}
+
+/// Checks that the tools used to build the project can be found and executed.
+pub fn execute(options: &Options) -> Result<()> {
+	let project = Project::load(
+		options.project_directory,
+		options.target,
+		options.profile,
+		None,
+		options.tool_overrides,
+	)?;
+	let settings = &project.settings;
+
+	let doctor = Doctor {
+		configuration: &project.configuration,
+		is_optional: Cell::new(false),
+		problem_count: Cell::new(0),
+	};
+
+	let audio_synthesizer = doctor.check_instance(
+		"audio-synthesizer",
+		settings
+			.audio_synthesizer
+			.instantiate(&project, options.target),
+	);
+	if options.target == BuildTarget::Executable {
+		doctor.check_tools(&settings.audio_synthesizer.get_tool_names());
+	}
+
+	let target_code_generator = doctor.check_instance(
+		"target-code-generator",
+		build::instantiate_target_code_generator(&project, options.target),
+	);
+	// When the target code generator fails to instantiate, GLEW is checked as if it used it.
+	match &target_code_generator {
+		Some(target_code_generator) => doctor.check_tools(&target_code_generator.get_tool_names()),
+		None => doctor.check_tool("glew"),
+	}
+
+	let (linker, linker_tool_names) = match options.target {
+		BuildTarget::Executable => (
+			settings.executable_linker.instantiate(&project),
+			settings.executable_linker.get_tool_names(),
+		),
+		BuildTarget::Library => (
+			settings.library_linker.instantiate(&project),
+			settings.library_linker.get_tool_names(),
+		),
+	};
+	doctor.check_instance("linker", linker);
+	doctor.check_tools(&linker_tool_names);
+
+	// When a stage fails to instantiate, compilers are checked as if it required them.
+	let emitters: Vec<Option<&dyn CompilationJobEmitter>> = vec![
+		audio_synthesizer
+			.as_ref()
+			.map(|instance| instance.as_ref() as &dyn CompilationJobEmitter),
+		target_code_generator
+			.as_ref()
+			.map(|instance| instance.as_ref() as &dyn CompilationJobEmitter),
+	];
+
+	if emitters
+		.iter()
+		.any(|emitter| emitter.is_none_or(|emitter| emitter.requires_asm_compiler()))
+	{
+		doctor.check_instance("asm-compiler", settings.asm_compiler.instantiate(&project));
+		doctor.check_tools(&settings.asm_compiler.get_tool_names());
+	}
+
+	if !settings.sources.is_empty()
+		|| emitters
+			.iter()
+			.any(|emitter| emitter.is_none_or(|emitter| emitter.requires_cpp_compiler()))
+	{
+		doctor.check_instance("cpp-compiler", settings.cpp_compiler.instantiate(&project));
+		doctor.check_tools(&settings.cpp_compiler.get_tool_names());
+	}
+
+	if let Some(shader_minifier) = &settings.shader_minifier {
+		doctor.check_instance("shader-minifier", shader_minifier.instantiate(&project));
+		doctor.check_tools(&shader_minifier.get_tool_names());
+	}
+
+	// Only exports to 7z and zip archives need 7z.
+	println!("export");
+	doctor.check_optional_tool("7z");
+
+	match doctor.problem_count.get() {
+		0 => {
+			println!("No problems found.");
+			Ok(())
+		}
+		problem_count => Err(Error::message(format!(
+			"Found {} problem(s).",
+			problem_count
+		))),
+	}
+}
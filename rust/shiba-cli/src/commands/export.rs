@@ -1,19 +1,61 @@
 use crate::build::{self, BuildEvent, BuildOptions, BuildTarget};
+use crate::compilation::Platform;
 use crate::export::{self, ExportOptions, ExportOutput};
 use crate::project_data::Project;
 use crate::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct Options<'a> {
 	pub export_directory: &'a Path,
 	pub force: bool,
+	pub jobs: Option<usize>,
 	pub output: ExportOutput,
+	/// Platforms to export, each in its own subdirectory when there are several.
+	pub platforms: &'a [Platform],
+	pub profile: Option<&'a str>,
 	pub project_directory: &'a Path,
 	pub target: BuildTarget,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
+	/// Overrides of the template variables.
+	pub variables: &'a [(String, serde_json::Value)],
 }
 
 pub fn execute(options: &Options) -> Result<()> {
-	let project = Project::load(options.project_directory, options.target)?;
+	// Without explicit platforms, the settings or the tools decide.
+	if options.platforms.is_empty() {
+		return export_platform(options, None, options.export_directory);
+	}
+
+	for platform in options.platforms {
+		let export_directory = if options.platforms.len() > 1 {
+			options.export_directory.join(platform.get_name())
+		} else {
+			options.export_directory.to_path_buf()
+		};
+		export_platform(options, Some(*platform), &export_directory)?;
+	}
+
+	Ok(())
+}
+
+// Each platform is built with its own settings, see the platforms section.
+fn export_platform(
+	options: &Options,
+	platform: Option<Platform>,
+	export_directory: &Path,
+) -> Result<()> {
+	let mut project = Project::load(
+		options.project_directory,
+		options.target,
+		options.profile,
+		platform,
+		options.tool_overrides,
+	)?;
+	project
+		.settings
+		.variables
+		.extend(options.variables.iter().cloned());
 
 	let mut build_path = None;
 	let mut static_files = None;
@@ -36,7 +78,10 @@ pub fn execute(options: &Options) -> Result<()> {
 
 	build::build(
 		&BuildOptions {
+			explain: false,
 			force: options.force,
+			jobs: options.jobs,
+			platform,
 			project: &project,
 			target: options.target,
 		},
@@ -45,7 +90,7 @@ pub fn execute(options: &Options) -> Result<()> {
 
 	export::export(&ExportOptions {
 		build_path: &build_path.unwrap(),
-		directory: options.export_directory,
+		directory: export_directory,
 		project: &project,
 		output: options.output,
 		static_files: &static_files.unwrap(),
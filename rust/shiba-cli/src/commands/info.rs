@@ -0,0 +1,371 @@
+use crate::build::{self, BuildOptions, BuildTarget};
+use crate::project_data::Project;
+use crate::settings::Settings;
+use crate::shader_data::{ShaderConstVariable, ShaderSet, ShaderVariable, ShaderVariableKind};
+use crate::target_code_generators::TargetCodeGenerator;
+use crate::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub struct Options<'a> {
+	/// Prints the information as JSON, for tools.
+	pub json: bool,
+	pub profile: Option<&'a str>,
+	pub project_directory: &'a Path,
+	pub target: BuildTarget,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ToolPath {
+	name: &'static str,
+	path: String,
+	source: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct StageInfo {
+	paths: Vec<ToolPath>,
+	stage: &'static str,
+	tool: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Info<'a> {
+	code_slots: BTreeMap<&'a str, Option<String>>,
+	name: &'a str,
+	settings: &'a Settings,
+	shader_set: &'a ShaderSet,
+	stages: Vec<StageInfo>,
+	static_files: Vec<String>,
+	target: BuildTarget,
+	/// Codes which fill no slot of the target code generator.
+	unknown_codes: BTreeMap<&'a str, String>,
+}
+
+// Stage settings are tagged by their tool.
+fn get_tool(settings: &impl Serialize) -> Option<String> {
+	serde_json::to_value(settings)
+		.ok()
+		.and_then(|value| value.get("tool")?.as_str().map(|tool| tool.to_string()))
+}
+
+fn get_stages(
+	project: &Project,
+	target: BuildTarget,
+	target_code_generator: &dyn TargetCodeGenerator,
+) -> Vec<StageInfo> {
+	let settings = &project.settings;
+
+	let mut stages = vec![];
+	let mut push_stage = |stage, tool, names: Vec<&'static str>| {
+		let paths = names
+			.into_iter()
+			.map(|name| {
+				let configured_path = project.configuration.get_configured_path(name);
+				ToolPath {
+					name,
+					path: configured_path.path.to_string_lossy().to_string(),
+					source: configured_path.source.to_string(),
+				}
+			})
+			.collect();
+		stages.push(StageInfo { paths, stage, tool });
+	};
+
+	// Libraries are built without audio.
+	match target {
+		BuildTarget::Executable => push_stage(
+			"audio-synthesizer",
+			get_tool(&settings.audio_synthesizer),
+			settings.audio_synthesizer.get_tool_names(),
+		),
+		BuildTarget::Library => push_stage("audio-synthesizer", Some("none".to_string()), vec![]),
+	}
+	push_stage(
+		"shader-provider",
+		get_tool(&settings.shader_provider),
+		vec![],
+	);
+	if let Some(shader_minifier) = &settings.shader_minifier {
+		push_stage(
+			"shader-minifier",
+			get_tool(shader_minifier),
+			shader_minifier.get_tool_names(),
+		);
+	}
+	match target {
+		BuildTarget::Executable => push_stage(
+			"target-code-generator",
+			get_tool(&settings.executable_target_code_generator),
+			target_code_generator.get_tool_names(),
+		),
+		BuildTarget::Library => push_stage(
+			"target-code-generator",
+			Some("library".to_string()),
+			target_code_generator.get_tool_names(),
+		),
+	}
+	push_stage(
+		"asm-compiler",
+		get_tool(&settings.asm_compiler),
+		settings.asm_compiler.get_tool_names(),
+	);
+	push_stage(
+		"cpp-compiler",
+		get_tool(&settings.cpp_compiler),
+		settings.cpp_compiler.get_tool_names(),
+	);
+	match target {
+		BuildTarget::Executable => push_stage(
+			"linker",
+			get_tool(&settings.executable_linker),
+			settings.executable_linker.get_tool_names(),
+		),
+		BuildTarget::Library => push_stage(
+			"linker",
+			get_tool(&settings.library_linker),
+			settings.library_linker.get_tool_names(),
+		),
+	}
+	push_stage("export", None, vec!["7z"]);
+
+	stages
+}
+
+fn print_code(indentation: &str, code: &str) {
+	for line in code.lines() {
+		println!("{}{}", indentation, line);
+	}
+}
+
+fn describe_variable(shader_set: &ShaderSet, variable: &ShaderVariable) -> String {
+	let declaration = match variable.length {
+		Some(length) => format!("{} {}[{}]", variable.type_name, variable.name, length),
+		None => format!("{} {}", variable.type_name, variable.name),
+	};
+
+	let state = match &variable.kind {
+		ShaderVariableKind::Const(ShaderConstVariable { value }) if !variable.active => {
+			format!("const, inlined as {}", value)
+		}
+		ShaderVariableKind::Const(ShaderConstVariable { value }) => format!("const = {}", value),
+		_ if !variable.active => "unreferenced".to_string(),
+		ShaderVariableKind::Regular => "regular".to_string(),
+		ShaderVariableKind::Uniform(_) => shader_set
+			.uniform_arrays
+			.iter()
+			.find_map(|uniform_array| {
+				let index = uniform_array
+					.variables
+					.iter()
+					.position(|packed_variable| packed_variable.name == variable.name)?;
+				Some(format!(
+					"uniform, packed into {}[{}]",
+					uniform_array
+						.minified_name
+						.as_ref()
+						.unwrap_or(&uniform_array.name),
+					index
+				))
+			})
+			.unwrap_or_else(|| "uniform".to_string()),
+	};
+
+	match &variable.minified_name {
+		Some(minified_name) => format!("{}: {}, minified as {}", declaration, state, minified_name),
+		None => format!("{}: {}", declaration, state),
+	}
+}
+
+fn print_info(info: &Info) {
+	println!("Project: {}", info.name);
+	println!("Target: {:?}", info.target);
+
+	println!("Settings:");
+	let settings = serde_yaml::to_string(info.settings).expect("Failed to dump YAML.");
+	print_code("  ", settings.trim_start_matches("---\n"));
+
+	println!("Stages:");
+	for stage in &info.stages {
+		match &stage.tool {
+			Some(tool) => println!("  {}: {}", stage.stage, tool),
+			None => println!("  {}", stage.stage),
+		}
+		for tool_path in &stage.paths {
+			println!(
+				"    {}: {} ({})",
+				tool_path.name, tool_path.path, tool_path.source
+			);
+		}
+	}
+
+	println!("Code slots:");
+	for (slot, path) in &info.code_slots {
+		match path {
+			Some(path) => println!("  {}: {}", slot, path),
+			None => println!("  {}: (empty)", slot),
+		}
+	}
+
+	if !info.unknown_codes.is_empty() {
+		println!("Codes filling no slot:");
+		for (name, path) in &info.unknown_codes {
+			println!("  {}: {}", name, path);
+		}
+	}
+
+	let shader_set = info.shader_set;
+	println!("Shader set:");
+	if let Some(glsl_version) = &shader_set.glsl_version {
+		println!("  GLSL version: {}", glsl_version);
+	}
+
+	println!("  Programs:");
+	for (name, program) in &shader_set.programs {
+		println!("    {}:", name);
+		for (stage, code) in &[("vertex", &program.vertex), ("fragment", &program.fragment)] {
+			if let Some(code) = code {
+				println!("      {}:", stage);
+				print_code("        ", code);
+			}
+		}
+	}
+
+	let sections = &shader_set.sections;
+	println!("  Sections:");
+	for (name, code) in &[
+		("attributes", &sections.attributes),
+		("common", &sections.common),
+		("outputs", &sections.outputs),
+		("varyings", &sections.varyings),
+	] {
+		match code {
+			Some(code) => {
+				println!("    {}:", name);
+				print_code("      ", code);
+			}
+			None => println!("    {}: (empty)", name),
+		}
+	}
+
+	println!("  Variables:");
+	for variable in &shader_set.variables {
+		println!("    {}", describe_variable(shader_set, variable));
+	}
+
+	println!("  Uniform arrays:");
+	for uniform_array in &shader_set.uniform_arrays {
+		match &uniform_array.minified_name {
+			Some(minified_name) => println!(
+				"    {} {}, minified as {}:",
+				uniform_array.type_name, uniform_array.name, minified_name
+			),
+			None => println!("    {} {}:", uniform_array.type_name, uniform_array.name),
+		}
+		for (index, variable) in uniform_array.variables.iter().enumerate() {
+			println!("      [{}] {}", index, variable.name);
+		}
+	}
+
+	println!("Static files:");
+	for path in &info.static_files {
+		println!("  {}", path);
+	}
+}
+
+pub fn execute(options: &Options) -> Result<()> {
+	let project = Project::load(
+		options.project_directory,
+		options.target,
+		options.profile,
+		None,
+		options.tool_overrides,
+	)?;
+	let settings = &project.settings;
+
+	let audio_synthesizer = settings
+		.audio_synthesizer
+		.instantiate(&project, options.target)?;
+	let shader_provider = settings.shader_provider.instantiate(&project)?;
+	let shader_minifier = settings
+		.shader_minifier
+		.as_ref()
+		.map(|shader_minifier| shader_minifier.instantiate(&project))
+		.transpose()?;
+	let target_code_generator = build::instantiate_target_code_generator(&project, options.target)?;
+	let slots = target_code_generator.get_code_slots();
+
+	let project_files = build::load_project_files(
+		&project,
+		vec![
+			audio_synthesizer.get_is_path_handled(),
+			shader_provider.get_is_path_handled(),
+		],
+	)?;
+	let code_paths = project_files.get_code_paths()?;
+
+	// The shader set is the one given to the target code generator. Its cache entries are shared with builds, but last builds are only recorded by builds.
+	let build_options = BuildOptions {
+		explain: false,
+		force: false,
+		jobs: None,
+		platform: None,
+		project: &project,
+		target: options.target,
+	};
+	let mut shader_set = shader_provider.provide(&build_options)?;
+	if let Some(shader_minifier) = shader_minifier {
+		shader_set = shader_minifier.minify(&build_options, &shader_set)?;
+	}
+
+	let get_relative_path = |path: &Path| {
+		path.strip_prefix(&project.directory)
+			.unwrap_or(path)
+			.to_string_lossy()
+			.to_string()
+	};
+
+	let info = Info {
+		code_slots: slots
+			.iter()
+			.map(|slot| {
+				(
+					*slot,
+					code_paths.get(*slot).map(|path| get_relative_path(path)),
+				)
+			})
+			.collect(),
+		name: &settings.name,
+		settings,
+		shader_set: &shader_set,
+		stages: get_stages(&project, options.target, target_code_generator.as_ref()),
+		static_files: project_files
+			.get_static_files()
+			.iter()
+			.map(|path| get_relative_path(path))
+			.collect(),
+		target: options.target,
+		unknown_codes: code_paths
+			.iter()
+			.filter(|(name, _)| !slots.contains(&name.as_str()))
+			.map(|(name, path)| (name.as_str(), get_relative_path(path)))
+			.collect(),
+	};
+
+	if options.json {
+		println!(
+			"{}",
+			serde_json::to_string_pretty(&info).expect("Failed to dump JSON.")
+		);
+	} else {
+		print_info(&info);
+	}
+
+	Ok(())
+}
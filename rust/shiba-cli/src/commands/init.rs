@@ -0,0 +1,69 @@
+use crate::project_templates::{self, TemplateContext};
+use crate::settings_migration;
+use crate::{Error, Result};
+use std::fs;
+use std::path::Path;
+
+pub struct Options<'a> {
+	pub force: bool,
+	pub list: bool,
+	/// Defaults to the name of the project directory.
+	pub name: Option<&'a str>,
+	pub project_directory: &'a Path,
+	pub template: &'a str,
+}
+
+pub fn execute(options: &Options) -> Result<()> {
+	let templates = project_templates::get_templates()?;
+
+	if options.list {
+		for (name, template) in &templates {
+			println!("{} ({})", name, template.describe());
+		}
+		println!(
+			"Add your own templates as directories in {}.",
+			project_templates::get_local_templates_directory().to_string_lossy()
+		);
+		return Ok(());
+	}
+
+	let template = templates.get(options.template).ok_or_else(|| {
+		Error::message(format!(
+			"Unknown template {}, available templates: {}.",
+			options.template,
+			templates.keys().cloned().collect::<Vec<_>>().join(", ")
+		))
+	})?;
+
+	fs::create_dir_all(options.project_directory)
+		.map_err(|err| Error::failed_to_create_directory(options.project_directory, err))?;
+	let project_directory = fs::canonicalize(options.project_directory)
+		.map_err(|err| Error::failed_to_read(options.project_directory, err))?;
+
+	let name = match options.name {
+		Some(name) => name.to_string(),
+		None => project_directory
+			.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_else(|| "demo".to_string()),
+	};
+
+	let paths = template.instantiate(
+		&project_directory,
+		&TemplateContext {
+			name: &name,
+			shiba_version: &settings_migration::get_current_version().to_string(),
+		},
+		options.force,
+	)?;
+
+	println!(
+		"Created project {} from template {}:",
+		name, options.template
+	);
+	for path in paths {
+		println!("  {}", path.to_string_lossy());
+	}
+
+	Ok(())
+}
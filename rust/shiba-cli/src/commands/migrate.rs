@@ -0,0 +1,63 @@
+use crate::settings;
+use crate::settings_migration;
+use crate::settings_validation::Issue;
+use crate::{Error, Result};
+use serde_yaml::Value;
+use std::fs;
+use std::path::Path;
+
+pub struct Options<'a> {
+	pub project_directory: &'a Path,
+}
+
+pub fn execute(options: &Options) -> Result<()> {
+	let mut path = options.project_directory.join(settings::FILENAME);
+	let mut visited_paths = vec![];
+
+	loop {
+		let canonical_path =
+			fs::canonicalize(&path).map_err(|err| Error::failed_to_read(&path, err))?;
+		if visited_paths.contains(&canonical_path) {
+			break;
+		}
+		visited_paths.push(canonical_path);
+
+		let contents =
+			fs::read_to_string(&path).map_err(|err| Error::failed_to_read(&path, err))?;
+		let original_value: Value = serde_yaml::from_str(&contents)
+			.map_err(|err| Error::message(Issue::from_yaml_error(&path, &err)))?;
+
+		let mut value = original_value.clone();
+		let descriptions = settings_migration::migrate(&path, &mut value)?;
+
+		if value == original_value {
+			println!("{} is up to date.", path.to_string_lossy());
+		} else {
+			// Comments and formatting are not preserved, the original file is kept aside.
+			let backup_path = path.with_extension("yml.bak");
+			fs::write(&backup_path, &contents)
+				.map_err(|err| Error::failed_to_write(&backup_path, err))?;
+
+			let migrated_contents = serde_yaml::to_string(&value).expect("Failed to dump YAML.");
+			fs::write(&path, migrated_contents)
+				.map_err(|err| Error::failed_to_write(&path, err))?;
+
+			println!(
+				"Migrated {} to shiba {}, previous version kept in {}.",
+				path.to_string_lossy(),
+				settings_migration::get_current_version(),
+				backup_path.to_string_lossy()
+			);
+			for description in descriptions {
+				println!("  {}", description);
+			}
+		}
+
+		match value.get("extends").and_then(|extends| extends.as_str()) {
+			Some(extends) => path = settings::get_extended_path(&path, extends),
+			None => break,
+		}
+	}
+
+	Ok(())
+}
@@ -2,14 +2,29 @@ use crate::build::{self, BuildEvent, BuildOptions, BuildTarget};
 use crate::project_data::Project;
 use crate::run::{self, RunOptions};
 use crate::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct Options<'a> {
+	pub profile: Option<&'a str>,
 	pub project_directory: &'a Path,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
+	/// Overrides of the template variables.
+	pub variables: &'a [(String, serde_json::Value)],
 }
 
 pub fn execute(options: &Options) -> Result<()> {
-	let project = Project::load(options.project_directory, BuildTarget::Executable)?;
+	let mut project = Project::load(
+		options.project_directory,
+		BuildTarget::Executable,
+		options.profile,
+		None,
+		options.tool_overrides,
+	)?;
+	project
+		.settings
+		.variables
+		.extend(options.variables.iter().cloned());
 
 	let mut executable_path = None;
 
@@ -21,7 +36,10 @@ pub fn execute(options: &Options) -> Result<()> {
 
 	build::build(
 		&BuildOptions {
+			explain: false,
 			force: false,
+			jobs: None,
+			platform: None,
 			project: &project,
 			target: BuildTarget::Executable,
 		},
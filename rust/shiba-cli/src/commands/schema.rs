@@ -0,0 +1,21 @@
+use crate::settings_validation;
+use crate::{Error, Result};
+use std::fs;
+use std::path::Path;
+
+pub struct Options<'a> {
+	pub output: Option<&'a Path>,
+}
+
+pub fn execute(options: &Options) -> Result<()> {
+	let schema = settings_validation::get_schema();
+	let json = serde_json::to_string_pretty(&schema).expect("Failed to dump JSON.");
+
+	match options.output {
+		Some(path) => fs::write(path, json).map_err(|err| Error::failed_to_write(path, err)),
+		None => {
+			println!("{}", json);
+			Ok(())
+		}
+	}
+}
@@ -1,4 +1,6 @@
 use crate::build::{self, BuildEvent, BuildOptions, BuildTarget};
+use crate::build_cache::Explanation;
+use crate::compilation::Platform;
 use crate::export::{self, ExportOptions, ExportOutput};
 use crate::project_data::Project;
 use crate::run::{self, RunOptions};
@@ -10,7 +12,8 @@ use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader, Write};
 use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
-use std::sync::{mpsc::channel, Arc, RwLock};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, RwLock};
 use std::thread::spawn;
 use std::time::Duration;
 
@@ -19,6 +22,8 @@ pub struct Options<'a> {
 	pub ip: IpAddr,
 	pub port: u16,
 	pub project_directory: &'a Path,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,6 +31,8 @@ pub struct Options<'a> {
 enum CommandKind {
 	Build {
 		force: Option<bool>,
+		jobs: Option<usize>,
+		platform: Option<Platform>,
 		target: BuildTarget,
 	},
 	Export {
@@ -38,6 +45,9 @@ enum CommandKind {
 		executable: bool,
 		library: bool,
 	},
+	SetProfile {
+		profile: Option<String>,
+	},
 	SetProjectDirectory {
 		path: String,
 	},
@@ -68,8 +78,14 @@ enum EventKind<'a> {
 		successful: bool,
 	},
 	BuildStarted,
+	CacheChecked {
+		#[serde(flatten)]
+		explanation: &'a Explanation,
+		target: BuildTarget,
+	},
 	ExecutableBuilt {
 		path: &'a str,
+		platform: Platform,
 		size: u64,
 	},
 	Exported {
@@ -80,6 +96,7 @@ enum EventKind<'a> {
 	},
 	LibraryBuilt {
 		path: &'a str,
+		platform: Platform,
 	},
 	Run {
 		duration: f32,
@@ -89,6 +106,16 @@ enum EventKind<'a> {
 		target: BuildTarget,
 		variables: &'a Vec<ShaderVariable>,
 	},
+	StageFinished {
+		duration: f32,
+		stage: &'a str,
+		successful: bool,
+		target: BuildTarget,
+	},
+	StageStarted {
+		stage: &'a str,
+		target: BuildTarget,
+	},
 }
 
 #[derive(Debug, Serialize)]
@@ -118,6 +145,38 @@ impl State {
 	}
 }
 
+/// Watches the directory and its subdirectories, sending events once they have settled for the debounce delay.
+pub fn watch_directory(
+	directory: &Path,
+	debounce_delay: Duration,
+	tx: Sender<DebouncedEvent>,
+) -> Result<RecommendedWatcher> {
+	let mut watcher: RecommendedWatcher = Watcher::new(tx, debounce_delay)
+		.map_err(|err| Error::message(format!("Failed to create watcher: {}", err)))?;
+	watcher
+		.watch(directory, RecursiveMode::Recursive)
+		.map_err(|err| {
+			Error::message(format!(
+				"Failed to watch directory '{}': {}",
+				directory.to_string_lossy(),
+				err
+			))
+		})?;
+	Ok(watcher)
+}
+
+/// Tells whether the event is a change of files, which requires to build again.
+pub fn is_change_event(event: &DebouncedEvent) -> bool {
+	matches!(
+		event,
+		DebouncedEvent::Create(_)
+			| DebouncedEvent::Remove(_)
+			| DebouncedEvent::Rename(_, _)
+			| DebouncedEvent::Rescan
+			| DebouncedEvent::Write(_)
+	)
+}
+
 pub fn execute(options: &Options) -> Result<()> {
 	let addr = SocketAddr::new(options.ip, options.port);
 	let listener =
@@ -131,6 +190,9 @@ pub fn execute(options: &Options) -> Result<()> {
 	let (tx_command, rx_command) = channel::<Command>();
 	let command_state = state.clone();
 	let mut command_project_directory = options.project_directory.to_path_buf();
+	let mut command_profile: Option<String> = None;
+	let tool_overrides = options.tool_overrides.to_vec();
+	let debounce_delay = options.debounce_delay;
 	spawn(move || {
 		#[derive(Default)]
 		struct BuildTargetArtifacts {
@@ -139,11 +201,7 @@ pub fn execute(options: &Options) -> Result<()> {
 			static_files: Option<Vec<PathBuf>>,
 		}
 
-		let mut watcher: RecommendedWatcher =
-			Watcher::new(tx_watcher, Duration::from_secs_f32(0.3))
-				.expect("Failed to create watcher.");
-		watcher
-			.watch(command_project_directory.clone(), RecursiveMode::Recursive)
+		let mut watcher = watch_directory(&command_project_directory, debounce_delay, tx_watcher)
 			.expect("Failed to watch project directory");
 
 		let mut executable_artifacts = BuildTargetArtifacts::default();
@@ -154,7 +212,12 @@ pub fn execute(options: &Options) -> Result<()> {
 				Ok(command) => {
 					let command_id = command.id.clone();
 					match command.kind {
-						CommandKind::Build { force, target } => {
+						CommandKind::Build {
+							force,
+							jobs,
+							platform,
+							target,
+						} => {
 							{
 								let mut command_state = command_state.write().unwrap();
 								command_state.broadcast(&Event {
@@ -163,9 +226,26 @@ pub fn execute(options: &Options) -> Result<()> {
 								});
 							}
 
-							match Project::load(&command_project_directory, target) {
+							match Project::load(
+								&command_project_directory,
+								target,
+								command_profile.as_deref(),
+								platform,
+								&tool_overrides,
+							) {
 								Ok(project) => {
 									let mut event_listener = |event: BuildEvent| match event {
+										BuildEvent::CacheChecked(event) => {
+											let mut command_state = command_state.write().unwrap();
+											command_state.broadcast(&Event {
+												id: &command_id,
+												kind: EventKind::CacheChecked {
+													explanation: event.explanation,
+													target,
+												},
+											});
+										}
+
 										BuildEvent::ExecutableBuilt(event) => {
 											match event.get_size() {
 												Ok(size) => {
@@ -177,6 +257,7 @@ pub fn execute(options: &Options) -> Result<()> {
 														id: &command_id,
 														kind: EventKind::ExecutableBuilt {
 															path: &path,
+															platform: event.platform,
 															size,
 														},
 													});
@@ -204,7 +285,10 @@ pub fn execute(options: &Options) -> Result<()> {
 													command_state.write().unwrap();
 												command_state.broadcast(&Event {
 													id: &command_id,
-													kind: EventKind::LibraryBuilt { path: &path },
+													kind: EventKind::LibraryBuilt {
+														path: &path,
+														platform: event.platform,
+													},
 												});
 											}
 											library_artifacts.path = Some(event.path.to_path_buf());
@@ -224,6 +308,30 @@ pub fn execute(options: &Options) -> Result<()> {
 											});
 										}
 
+										BuildEvent::StageFinished(event) => {
+											let mut command_state = command_state.write().unwrap();
+											command_state.broadcast(&Event {
+												id: &command_id,
+												kind: EventKind::StageFinished {
+													duration: event.duration.as_secs_f32(),
+													stage: event.stage,
+													successful: event.successful,
+													target,
+												},
+											});
+										}
+
+										BuildEvent::StageStarted(event) => {
+											let mut command_state = command_state.write().unwrap();
+											command_state.broadcast(&Event {
+												id: &command_id,
+												kind: EventKind::StageStarted {
+													stage: event.stage,
+													target,
+												},
+											});
+										}
+
 										BuildEvent::StaticFilesProvided(event) => match target {
 											BuildTarget::Executable => {
 												executable_artifacts.static_files =
@@ -238,7 +346,10 @@ pub fn execute(options: &Options) -> Result<()> {
 
 									let result = build::build_duration(
 										&BuildOptions {
+											explain: true,
 											force: force.unwrap_or(false),
+											jobs,
+											platform,
 											project: &project,
 											target,
 										},
@@ -418,6 +529,10 @@ pub fn execute(options: &Options) -> Result<()> {
 							command_state.library_build_on_change = library;
 						}
 
+						CommandKind::SetProfile { profile } => {
+							command_profile = profile;
+						}
+
 						CommandKind::SetProjectDirectory { path } => {
 							if let Err(err) = watcher.unwatch(&command_project_directory) {
 								println!("Failed to unwatch project directory: {}", err);
@@ -440,12 +555,8 @@ pub fn execute(options: &Options) -> Result<()> {
 	let watcher_tx_command = tx_command.clone();
 	spawn(move || loop {
 		match rx_watcher.recv() {
-			Ok(event) => match event {
-				DebouncedEvent::Create(_)
-				| DebouncedEvent::Remove(_)
-				| DebouncedEvent::Rename(_, _)
-				| DebouncedEvent::Rescan
-				| DebouncedEvent::Write(_) => {
+			Ok(event) => {
+				if is_change_event(&event) {
 					let watcher_state = watcher_state.read().unwrap();
 
 					if watcher_state.library_build_on_change {
@@ -453,6 +564,8 @@ pub fn execute(options: &Options) -> Result<()> {
 							id: None,
 							kind: CommandKind::Build {
 								force: None,
+								jobs: None,
+								platform: None,
 								target: BuildTarget::Library,
 							},
 						});
@@ -463,13 +576,14 @@ pub fn execute(options: &Options) -> Result<()> {
 							id: None,
 							kind: CommandKind::Build {
 								force: None,
+								jobs: None,
+								platform: None,
 								target: BuildTarget::Executable,
 							},
 						});
 					}
 				}
-				_ => {}
-			},
+			}
 			Err(err) => {
 				panic!("Error while watching: {}", err);
 			}
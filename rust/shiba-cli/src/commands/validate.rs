@@ -0,0 +1,25 @@
+use crate::settings_validation;
+use crate::{Error, Result};
+use std::path::Path;
+
+pub struct Options<'a> {
+	pub project_directory: &'a Path,
+}
+
+pub fn execute(options: &Options) -> Result<()> {
+	let issues = settings_validation::validate(options.project_directory)?;
+
+	if issues.is_empty() {
+		println!("Settings are valid.");
+		return Ok(());
+	}
+
+	for issue in &issues {
+		println!("{}", issue);
+	}
+
+	Err(Error::message(format!(
+		"Found {} issue(s) in settings.",
+		issues.len()
+	)))
+}
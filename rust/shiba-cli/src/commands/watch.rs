@@ -0,0 +1,175 @@
+use super::server;
+use crate::build::{self, BuildEvent, BuildOptions, BuildTarget};
+use crate::project_data::Project;
+use crate::run::{self, RunOptions};
+use crate::{Error, Result};
+use notify::RecommendedWatcher;
+use std::collections::BTreeSet;
+use std::error::Error as StdError;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+
+pub struct Options<'a> {
+	pub debounce_delay: Duration,
+	pub profile: Option<&'a str>,
+	pub project_directory: &'a Path,
+	/// Whether to relaunch the executable after each successful build.
+	pub run: bool,
+	pub target: BuildTarget,
+	/// Tool paths given on the command line, overriding the configuration.
+	pub tool_overrides: &'a [(String, PathBuf)],
+	/// Overrides of the template variables.
+	pub variables: &'a [(String, serde_json::Value)],
+}
+
+// Keeps watching the directories of the project, including the ones it extends.
+struct Watchers {
+	debounce_delay: Duration,
+	directories: BTreeSet<PathBuf>,
+	tx: Sender<notify::DebouncedEvent>,
+	watchers: Vec<RecommendedWatcher>,
+}
+
+impl Watchers {
+	fn watch(&mut self, directory: &Path) -> Result<()> {
+		if self.directories.insert(directory.to_path_buf()) {
+			self.watchers.push(server::watch_directory(
+				directory,
+				self.debounce_delay,
+				self.tx.clone(),
+			)?);
+		}
+		Ok(())
+	}
+}
+
+fn print_error(err: &Error) {
+	println!("Error: {}", err);
+
+	let mut source = err.source();
+	while let Some(err) = source {
+		println!("Caused by: {}", err);
+		source = err.source();
+	}
+}
+
+fn stop(process: &mut Option<Child>) {
+	if let Some(mut process) = process.take() {
+		// The process may already have exited.
+		let _ = process.kill();
+		let _ = process.wait();
+	}
+}
+
+// Returns the path of the executable or library built.
+fn build_project(options: &Options, watchers: &mut Watchers) -> Result<PathBuf> {
+	let mut project = Project::load(
+		options.project_directory,
+		options.target,
+		options.profile,
+		None,
+		options.tool_overrides,
+	)?;
+	project
+		.settings
+		.variables
+		.extend(options.variables.iter().cloned());
+
+	for directory in project.get_directories() {
+		watchers.watch(directory)?;
+	}
+
+	let mut built_path = None;
+
+	let mut event_listener = |event: BuildEvent| match event {
+		BuildEvent::ExecutableBuilt(event) => {
+			println!("Executable compiled:");
+			println!("  Path: {:?}", event.path);
+			println!("  Platform: {:?}", event.platform);
+			if let Ok(size) = event.get_size() {
+				println!("  Size: {}", size);
+			}
+			built_path = Some(event.path.to_path_buf());
+		}
+
+		BuildEvent::LibraryBuilt(event) => {
+			println!("Library compiled:");
+			println!("  Path: {:?}", event.path);
+			println!("  Platform: {:?}", event.platform);
+			built_path = Some(event.path.to_path_buf());
+		}
+
+		BuildEvent::StageFinished(event) if !event.successful => {
+			println!("Stage {} failed.", event.stage);
+		}
+
+		_ => {}
+	};
+
+	let duration = build::build_duration(
+		&BuildOptions {
+			explain: false,
+			force: false,
+			jobs: None,
+			platform: None,
+			project: &project,
+			target: options.target,
+		},
+		&mut event_listener,
+	)?;
+	println!("Build duration: {:?}.", duration);
+
+	Ok(built_path.expect("Build did not provide a path."))
+}
+
+/// Builds the project each time its files change, until interrupted.
+pub fn execute(options: &Options) -> Result<()> {
+	if options.run && options.target != BuildTarget::Executable {
+		return Err(Error::message("Only executables can be run."));
+	}
+
+	let (tx, rx) = channel();
+	let mut watchers = Watchers {
+		debounce_delay: options.debounce_delay,
+		directories: BTreeSet::new(),
+		tx,
+		watchers: vec![],
+	};
+	watchers.watch(options.project_directory)?;
+
+	let mut process = None;
+	loop {
+		// The running executable would prevent from writing the new one.
+		if options.run {
+			stop(&mut process);
+		}
+
+		match build_project(options, &mut watchers) {
+			Ok(path) if options.run => match run::spawn(&RunOptions {
+				executable_path: &path,
+				project_directory: options.project_directory,
+			}) {
+				Ok(child) => process = Some(child),
+				Err(err) => print_error(&err),
+			},
+			Ok(_) => {}
+			Err(err) => print_error(&err),
+		}
+
+		println!("Watching for changes...");
+
+		loop {
+			let event = rx
+				.recv()
+				.map_err(|err| Error::message(format!("Error while watching: {}", err)))?;
+			if server::is_change_event(&event) {
+				break;
+			}
+		}
+
+		// Changes happening meanwhile are gathered into the next build.
+		while rx.try_recv().is_ok() {}
+	}
+}
@@ -1,11 +1,47 @@
+use crate::{Error, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+	Clone, Copy, Debug, Deserialize, Eq, Hash, JsonSchema, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum Platform {
 	X64,
 	X86,
 }
 
+impl Platform {
+	/// Returns the name used in settings and on the command line.
+	pub fn get_name(self) -> &'static str {
+		match self {
+			Platform::X64 => "x64",
+			Platform::X86 => "x86",
+		}
+	}
+}
+
+impl FromStr for Platform {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s {
+			"x64" => Ok(Platform::X64),
+			"x86" => Ok(Platform::X86),
+			_ => Err(Error::message("Invalid platform variant.")),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum System {
+	Linux,
+	Windows,
+}
+
 pub trait CompilationJobEmitter {
 	fn requires_asm_compiler(&self) -> bool;
 	fn requires_cpp_compiler(&self) -> bool;
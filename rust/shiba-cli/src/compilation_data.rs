@@ -1,19 +1,18 @@
-use std::collections::BTreeSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
-#[derive(Default, Hash)]
+#[derive(Default, Serialize)]
 pub struct Common {
 	pub link_dependencies: BTreeSet<PathBuf>,
 	pub link_library_paths: BTreeSet<PathBuf>,
 }
 
-#[derive(Hash)]
 pub enum CompilationJobKind {
 	Asm,
 	Cpp,
 }
 
-#[derive(Hash)]
 pub struct CompilationJob {
 	pub kind: CompilationJobKind,
 	pub path: PathBuf,
@@ -21,7 +20,7 @@ pub struct CompilationJob {
 	pub include_paths: BTreeSet<PathBuf>,
 }
 
-#[derive(Default, Hash)]
+#[derive(Default)]
 pub struct Compilation {
 	pub jobs: Vec<CompilationJob>,
 	pub include_paths: BTreeSet<PathBuf>,
@@ -29,9 +28,11 @@ pub struct Compilation {
 	pub common: Common,
 }
 
-#[derive(Default, Hash)]
+#[derive(Default, Serialize)]
 pub struct Linking {
 	pub sources: Vec<PathBuf>,
+	/// Prebuilt files are not in the build cache, so their contents are part of the inputs.
+	pub prebuilt_digests: BTreeMap<PathBuf, String>,
 
 	pub common: Common,
 }
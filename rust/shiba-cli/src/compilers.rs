@@ -4,20 +4,30 @@ use crate::compilation_data::Linking;
 use crate::project_files::CodeMap;
 use crate::shader_data::ShaderSet;
 use crate::Result;
-use std::collections::BTreeSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
-#[derive(Hash)]
+#[derive(Serialize)]
 pub struct CompileOptions<'a> {
-	pub audio_codes: &'a CodeMap,
+	/// Codes the generated jobs are made of, none for project sources which do not include them.
+	pub generated_codes: Option<GeneratedCodes<'a>>,
+	/// Project headers may be included by any job, so they are part of the inputs.
+	pub header_digests: &'a BTreeMap<PathBuf, String>,
 	pub include_paths: &'a BTreeSet<PathBuf>,
 	pub path: &'a Path,
 	pub platform: Platform,
+}
+
+#[derive(Serialize)]
+pub struct GeneratedCodes<'a> {
+	pub audio_codes: &'a CodeMap,
 	pub project_codes: &'a CodeMap,
 	pub shader_set: &'a ShaderSet,
 }
 
-pub trait Compiler: PlatformDependent {
+// Compile jobs run in parallel.
+pub trait Compiler: PlatformDependent + Sync {
 	fn compile(
 		&self,
 		build_options: &BuildOptions,
@@ -1,37 +1,264 @@
 use crate::paths::USER_SETTINGS_DIRECTORY;
+use crate::settings_validation::Issue;
 use crate::{Error, Result};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub type ConfigurationPaths = HashMap<String, PathBuf>;
+/// Prefix of the environment variables overriding tool paths, e.g. SHIBA_TOOL_SHADER_MINIFIER.
+///
+/// Names are uppercased, with "-" written as "_" and "+" as "X", e.g. SHIBA_TOOL_GXX for g++.
+pub const ENVIRONMENT_VARIABLE_PREFIX: &str = "SHIBA_TOOL_";
+
+/// Names of the tools looked up in the configuration.
+pub const TOOL_NAMES: &[&str] = &[
+	"7z",
+	"clang++",
+	"crinkler",
+	"g++",
+	"glew",
+	"i686-w64-mingw32-g++",
+	"nasm",
+	"oidos",
+	"python2",
+	"shader-minifier",
+	"x86_64-w64-mingw32-g++",
+];
+
+/// Where a tool path comes from, by increasing precedence.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigurationSource {
+	Default,
+	File(PathBuf),
+	EnvironmentVariable(String),
+	CommandLine,
+}
+
+impl fmt::Display for ConfigurationSource {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ConfigurationSource::Default => write!(f, "default"),
+			ConfigurationSource::File(path) => write!(f, "{}", path.to_string_lossy()),
+			ConfigurationSource::EnvironmentVariable(name) => write!(f, "${}", name),
+			ConfigurationSource::CommandLine => write!(f, "--tool"),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfiguredPath {
+	pub path: PathBuf,
+	pub source: ConfigurationSource,
+}
+
+pub type ConfigurationPaths = BTreeMap<String, ConfiguredPath>;
 
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
+struct ConfigurationFile {
+	#[serde(default)]
+	paths: BTreeMap<String, PathBuf>,
+}
+
+#[derive(Debug, Default)]
 pub struct Configuration {
 	paths: ConfigurationPaths,
 }
 
+/// Parses a tool override such as "nasm=C:/nasm/nasm.exe".
+pub fn parse_tool_override(definition: &str) -> Result<(String, PathBuf)> {
+	let mut parts = definition.splitn(2, '=');
+	let name = parts.next().unwrap_or_default().trim();
+	match parts.next() {
+		Some(path) if !name.is_empty() => Ok((name.to_string(), PathBuf::from(path))),
+		_ => Err(Error::message(format!(
+			"Tool override {} must be of the form name=path.",
+			definition
+		))),
+	}
+}
+
+fn escape_tool_name(name: &str) -> String {
+	name.chars()
+		.map(|c| match c {
+			'+' => 'X',
+			'-' => '_',
+			c => c.to_ascii_uppercase(),
+		})
+		.collect()
+}
+
+// Escaping is not reversible, so known tools are matched first.
+fn unescape_tool_name(escaped_name: &str) -> String {
+	TOOL_NAMES
+		.iter()
+		.find(|name| escape_tool_name(name) == escaped_name)
+		.map(|name| name.to_string())
+		.unwrap_or_else(|| escaped_name.to_lowercase().replace('_', "-"))
+}
+
+/// Returns the environment variable overriding the tool path, if the name can be written as one.
+pub fn get_environment_variable_name(name: &str) -> Option<String> {
+	let escaped_name = escape_tool_name(name);
+	let is_valid = escaped_name
+		.chars()
+		.all(|c| c.is_ascii_alphanumeric() || c == '_')
+		&& unescape_tool_name(&escaped_name) == name;
+	Some(format!("{}{}", ENVIRONMENT_VARIABLE_PREFIX, escaped_name)).filter(|_| is_valid)
+}
+
+// Expands "~", and makes relative paths relative to the base directory. Bare names are kept, so that they are looked up in the PATH.
+fn resolve_path(path: &Path, base_directory: &Path) -> PathBuf {
+	if let Ok(relative_path) = path.strip_prefix("~") {
+		if let Some(home_directory) = dirs::home_dir() {
+			return home_directory.join(relative_path);
+		}
+	}
+
+	if path.is_relative() && path.components().count() > 1 {
+		return base_directory.join(path);
+	}
+
+	path.to_path_buf()
+}
+
 impl<'a> Configuration {
-	pub fn load() -> Result<Self> {
-		let path = USER_SETTINGS_DIRECTORY.join("config.yml");
+	/// Loads, by increasing precedence: the user configuration, the project configuration, the environment variables, and the overrides given on the command line.
+	pub fn load(project_directory: &Path, tool_overrides: &[(String, PathBuf)]) -> Result<Self> {
+		let mut configuration = Configuration::default();
+		let current_directory = env::current_dir().unwrap_or_default();
 
-		if !path.exists() {
-			return Ok(Configuration::default());
+		configuration.load_file(&USER_SETTINGS_DIRECTORY.join("config.yml"))?;
+		configuration.load_file(
+			&current_directory
+				.join(project_directory)
+				.join(".shiba")
+				.join("config.yml"),
+		)?;
+
+		for (variable, value) in env::vars() {
+			if let Some(escaped_name) = variable.strip_prefix(ENVIRONMENT_VARIABLE_PREFIX) {
+				let name = unescape_tool_name(escaped_name);
+				configuration.paths.insert(
+					name,
+					ConfiguredPath {
+						path: resolve_path(Path::new(&value), &current_directory),
+						source: ConfigurationSource::EnvironmentVariable(variable),
+					},
+				);
+			}
+		}
+
+		for (name, path) in tool_overrides {
+			configuration.paths.insert(
+				name.clone(),
+				ConfiguredPath {
+					path: resolve_path(path, &current_directory),
+					source: ConfigurationSource::CommandLine,
+				},
+			);
 		}
 
-		let contents =
-			fs::read_to_string(&path).map_err(|err| Error::failed_to_read(&path, err))?;
-		let configuration: Configuration = serde_yaml::from_str(&contents)
-			.map_err(|err| Error::failed_to_deserialize(&contents, err))?;
 		Ok(configuration)
 	}
 
-	pub fn get_path(&'a self, name: &'a str) -> PathBuf {
+	fn load_file(&mut self, path: &Path) -> Result<()> {
+		if !path.exists() {
+			return Ok(());
+		}
+
+		let contents = fs::read_to_string(path).map_err(|err| Error::failed_to_read(path, err))?;
+		let file: ConfigurationFile = serde_yaml::from_str(&contents)
+			.map_err(|err| Error::message(Issue::from_yaml_error(path, &err)))?;
+
+		let directory = path.parent().unwrap_or_else(|| Path::new(""));
+		for (name, tool_path) in file.paths {
+			self.paths.insert(
+				name,
+				ConfiguredPath {
+					path: resolve_path(&tool_path, directory),
+					source: ConfigurationSource::File(path.to_path_buf()),
+				},
+			);
+		}
+		Ok(())
+	}
+
+	pub fn get_configured_path(&'a self, name: &'a str) -> ConfiguredPath {
 		match self.paths.get(name) {
-			Some(path) => path.clone(),
-			None => PathBuf::from(name),
+			Some(configured_path) => configured_path.clone(),
+			None => ConfiguredPath {
+				path: PathBuf::from(name),
+				source: ConfigurationSource::Default,
+			},
+		}
+	}
+
+	pub fn get_path(&'a self, name: &'a str) -> PathBuf {
+		self.get_configured_path(name).path
+	}
+
+	/// Returns the names of the known tools and of the configured ones.
+	pub fn get_names(&self) -> Vec<&str> {
+		let mut names = TOOL_NAMES.to_vec();
+		names.extend(self.paths.keys().map(|name| name.as_str()));
+		names.sort_unstable();
+		names.dedup();
+		names
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_resolve_path() {
+		let base_directory = Path::new("/projects/demo/.shiba");
+
+		assert_eq!(
+			resolve_path(Path::new("nasm"), base_directory),
+			PathBuf::from("nasm")
+		);
+		assert_eq!(
+			resolve_path(Path::new("../tools/nasm"), base_directory),
+			base_directory.join("../tools/nasm")
+		);
+		assert_eq!(
+			resolve_path(Path::new("/usr/bin/nasm"), base_directory),
+			PathBuf::from("/usr/bin/nasm")
+		);
+		assert_eq!(
+			resolve_path(Path::new("~/tools/nasm"), base_directory),
+			dirs::home_dir().unwrap().join("tools/nasm")
+		);
+	}
+
+	#[test]
+	fn test_get_environment_variable_name() {
+		assert_eq!(
+			get_environment_variable_name("shader-minifier").as_deref(),
+			Some("SHIBA_TOOL_SHADER_MINIFIER")
+		);
+		assert_eq!(
+			get_environment_variable_name("g++").as_deref(),
+			Some("SHIBA_TOOL_GXX")
+		);
+		assert_eq!(
+			get_environment_variable_name("x86_64-w64-mingw32-g++").as_deref(),
+			Some("SHIBA_TOOL_X86_64_W64_MINGW32_GXX")
+		);
+		assert_eq!(get_environment_variable_name("my_tool"), None);
+
+		for name in TOOL_NAMES {
+			let variable = get_environment_variable_name(name).unwrap();
+			assert_eq!(
+				unescape_tool_name(variable.strip_prefix(ENVIRONMENT_VARIABLE_PREFIX).unwrap()),
+				*name
+			);
 		}
 	}
 }
@@ -0,0 +1,156 @@
+mod settings;
+
+pub use self::settings::GccSettings;
+use crate::build::{BuildOptions, BuildTarget};
+use crate::build_cache;
+use crate::compilation::{Platform, PlatformDependent, System};
+use crate::compilation_data::Linking;
+use crate::compilers::{CompileOptions, Compiler};
+use crate::gcc;
+use crate::project_data::Project;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+
+pub struct GccCompiler<'a> {
+	settings: &'a GccSettings,
+
+	args: Vec<String>,
+	driver: gcc::Driver,
+	gcc_command_generator: gcc::CommandGenerator,
+}
+
+impl<'a> GccCompiler<'a> {
+	pub fn new(
+		project: &'a Project,
+		settings: &'a GccSettings,
+		driver: gcc::Driver,
+	) -> Result<Self> {
+		let args = settings.args.clone().unwrap_or_else(|| {
+			if project.development {
+				vec!["-g"]
+			} else {
+				vec![
+					"-Os",
+					"-fno-exceptions",
+					"-fno-rtti",
+					"-fno-stack-protector",
+					"-fomit-frame-pointer",
+					"-ffast-math",
+				]
+			}
+			.into_iter()
+			.map(|s| s.to_string())
+			.collect()
+		});
+		let gcc_command_generator = gcc::CommandGenerator::new(&project.configuration, driver);
+
+		Ok(GccCompiler {
+			settings,
+
+			args,
+			driver,
+			gcc_command_generator,
+		})
+	}
+}
+
+impl<'a> Compiler for GccCompiler<'a> {
+	fn compile(
+		&self,
+		build_options: &BuildOptions,
+		options: &CompileOptions,
+		linking: &mut Linking,
+	) -> Result<()> {
+		let output_filename = format!(
+			"{}.o",
+			options
+				.path
+				.file_name()
+				.ok_or_else(|| Error::path_has_invalid_file_name(options.path))?
+				.to_string_lossy()
+		);
+
+		let contents = fs::read_to_string(options.path)
+			.map_err(|err| Error::failed_to_read(options.path, err))?;
+
+		#[derive(Serialize)]
+		struct Inputs<'a> {
+			contents: &'a str,
+			gcc_command_generator: gcc::CommandGeneratorInputs<'a>,
+			options: &'a CompileOptions<'a>,
+			settings: &'a GccSettings,
+			target: BuildTarget,
+		}
+
+		let inputs = Inputs {
+			contents: &contents,
+			gcc_command_generator: self.gcc_command_generator.get_inputs(),
+			options,
+			settings: self.settings,
+			target: build_options.target,
+		};
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "cpp-compilers/gcc", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
+		let build_cache_path = build_cache_directory.join(output_filename);
+
+		linking.sources.push(build_cache_path.clone());
+
+		if build_cache_entry.is_hit_for_source(&build_cache_path, options.path) {
+			return Ok(());
+		}
+
+		let build_directory = build_cache_entry.create_scratch_directory()?;
+
+		let compiler_path = self.gcc_command_generator.get_path(options.platform);
+
+		let mut command = self.gcc_command_generator.command(options.platform);
+		if build_options.target == BuildTarget::Library && self.driver.get_system() == System::Linux
+		{
+			command.arg("-fPIC");
+		}
+
+		let mut compilation = command
+			.arg("-c")
+			.arg("-o")
+			.arg("file.o")
+			.args(&self.args)
+			.args(
+				options
+					.include_paths
+					.iter()
+					.map(|path| format!("-I{}", path.to_string_lossy())),
+			)
+			.arg(options.path)
+			.current_dir(&build_directory)
+			.spawn()
+			.map_err(|err| Error::failed_to_execute(compiler_path, err))?;
+
+		let status = compilation.wait().unwrap();
+		if !status.success() {
+			return Err(Error::execution_failed(compiler_path));
+		}
+
+		let copy_from = build_directory.join("file.o");
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
+
+		Ok(())
+	}
+}
+
+impl PlatformDependent for GccCompiler<'_> {
+	fn get_possible_platforms(&self) -> &'static BTreeSet<Platform> {
+		lazy_static! {
+			pub static ref POSSIBLE_PLATFORMS: BTreeSet<Platform> = {
+				let mut possible_platforms = BTreeSet::new();
+				possible_platforms.insert(Platform::X64);
+				possible_platforms.insert(Platform::X86);
+				possible_platforms
+			};
+		}
+
+		&*POSSIBLE_PLATFORMS
+	}
+}
@@ -1,3 +1,4 @@
+pub mod gcc;
 pub mod msvc;
 mod settings;
 
@@ -2,14 +2,14 @@ mod settings;
 
 pub use self::settings::MsvcSettings;
 use crate::build::BuildOptions;
+use crate::build_cache;
 use crate::compilation::{Platform, PlatformDependent};
 use crate::compilation_data::Linking;
 use crate::compilers::{CompileOptions, Compiler};
-use crate::hash_extra;
 use crate::msvc;
-use crate::paths::BUILD_ROOT_DIRECTORY;
 use crate::project_data::Project;
 use crate::{Error, Result};
+use serde::Serialize;
 use std::collections::BTreeSet;
 use std::fs;
 
@@ -70,7 +70,7 @@ impl<'a> Compiler for MsvcCompiler<'a> {
 		let contents = fs::read_to_string(options.path)
 			.map_err(|err| Error::failed_to_read(options.path, err))?;
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
 			contents: &'a str,
 			msvc_command_generator: msvc::CommandGeneratorInputs<'a>,
@@ -84,18 +84,18 @@ impl<'a> Compiler for MsvcCompiler<'a> {
 			options,
 			settings: self.settings,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "cpp-compilers/msvc", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(output_filename);
 
 		linking.sources.push(build_cache_path.clone());
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit_for_source(&build_cache_path, options.path) {
 			return Ok(());
 		}
 
-		let build_directory = BUILD_ROOT_DIRECTORY.join("cpp-compilers").join("msvc");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let mut compilation = self
 			.msvc_command_generator
@@ -123,8 +123,7 @@ impl<'a> Compiler for MsvcCompiler<'a> {
 		}
 
 		let copy_from = build_directory.join("file.obj");
-		fs::copy(&copy_from, &build_cache_path)
-			.map_err(|err| Error::failed_to_copy(&copy_from, &build_cache_path, err))?;
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
 
 		Ok(())
 	}
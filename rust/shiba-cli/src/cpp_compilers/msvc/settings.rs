@@ -1,6 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Deserialize, Hash, Serialize)]
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "MsvcCompilerSettings")]
 #[serde(rename_all = "kebab-case")]
 pub struct MsvcSettings {
 	#[serde(default)]
@@ -1,18 +1,43 @@
-use super::msvc;
+use super::{gcc, msvc};
 use crate::compilers::Compiler;
+use crate::gcc::Driver;
 use crate::project_data::Project;
 use crate::Result;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "CppCompilerSettings")]
 #[serde(rename_all = "kebab-case", tag = "tool")]
 pub enum Settings {
+	Clang(gcc::GccSettings),
+	Gcc(gcc::GccSettings),
+	Mingw(gcc::GccSettings),
 	Msvc(msvc::MsvcSettings),
 }
 
 impl Settings {
+	/// Returns the names of the tools to configure.
+	pub fn get_tool_names(&self) -> Vec<&'static str> {
+		match self {
+			Settings::Clang(_) => Driver::Clang.get_configuration_names(),
+			Settings::Gcc(_) => Driver::Gcc.get_configuration_names(),
+			Settings::Mingw(_) => Driver::Mingw.get_configuration_names(),
+			Settings::Msvc(_) => vec![],
+		}
+	}
+
 	pub fn instantiate<'a>(&'a self, project: &'a Project) -> Result<Box<(dyn Compiler + 'a)>> {
 		let instance: Box<(dyn Compiler + 'a)> = match self {
+			Settings::Clang(settings) => {
+				Box::new(gcc::GccCompiler::new(project, settings, Driver::Clang)?)
+			}
+			Settings::Gcc(settings) => {
+				Box::new(gcc::GccCompiler::new(project, settings, Driver::Gcc)?)
+			}
+			Settings::Mingw(settings) => {
+				Box::new(gcc::GccCompiler::new(project, settings, Driver::Mingw)?)
+			}
 			Settings::Msvc(settings) => Box::new(msvc::MsvcCompiler::new(project, settings)?),
 		};
 		Ok(instance)
@@ -15,12 +15,15 @@ pub enum ErrorKind {
 	FailedToExecute(PathBuf),
 	FailedToGetMetadata(PathBuf),
 	FailedToListenTCP(SocketAddr),
+	FailedToLock(PathBuf),
 	FailedToParse(String),
 	FailedToRead(PathBuf),
 	FailedToReadDirectory(PathBuf),
 	FailedToRemoveDirectory(PathBuf),
+	FailedToRename(PathBuf, PathBuf),
 	FailedToRenderTemplate(String),
 	FailedToWrite(PathBuf),
+	InvalidGlob(String),
 	Message(String),
 	PathHasInvalidFileName(PathBuf),
 }
@@ -98,6 +101,13 @@ impl Error {
 		}
 	}
 
+	pub fn failed_to_lock(path: impl Into<PathBuf>, source: ::std::io::Error) -> Self {
+		Error {
+			kind: ErrorKind::FailedToLock(path.into()),
+			source: Some(source.into()),
+		}
+	}
+
 	pub fn failed_to_parse(contents: impl ToString) -> Self {
 		Self {
 			kind: ErrorKind::FailedToParse(contents.to_string()),
@@ -126,6 +136,17 @@ impl Error {
 		}
 	}
 
+	pub fn failed_to_rename(
+		from: impl Into<PathBuf>,
+		to: impl Into<PathBuf>,
+		source: ::std::io::Error,
+	) -> Self {
+		Error {
+			kind: ErrorKind::FailedToRename(from.into(), to.into()),
+			source: Some(source.into()),
+		}
+	}
+
 	pub fn failed_to_render_template(name: &str, source: tera::Error) -> Self {
 		Error {
 			kind: ErrorKind::FailedToRenderTemplate(name.to_string()),
@@ -140,6 +161,13 @@ impl Error {
 		}
 	}
 
+	pub fn invalid_glob(pattern: &str, source: globset::Error) -> Self {
+		Error {
+			kind: ErrorKind::InvalidGlob(pattern.to_string()),
+			source: Some(source.into()),
+		}
+	}
+
 	pub fn message(text: impl ToString) -> Self {
 		Self {
 			kind: ErrorKind::Message(text.to_string()),
@@ -185,6 +213,9 @@ impl fmt::Display for Error {
 				write!(f, "Failed to get metadata '{}'.", path.to_string_lossy())
 			}
 			ErrorKind::FailedToListenTCP(addr) => write!(f, "Failed to listen to TCP '{}'.", addr),
+			ErrorKind::FailedToLock(path) => {
+				write!(f, "Failed to lock '{}'.", path.to_string_lossy())
+			}
 			ErrorKind::FailedToParse(contents) => write!(f, "Failed to parse '{}'.", contents),
 			ErrorKind::FailedToRead(path) => {
 				write!(f, "Failed to read '{}'.", path.to_string_lossy())
@@ -197,12 +228,19 @@ impl fmt::Display for Error {
 				"Failed to remove directory '{}'.",
 				path.to_string_lossy(),
 			),
+			ErrorKind::FailedToRename(from, to) => write!(
+				f,
+				"Failed to rename '{}' to '{}'.",
+				from.to_string_lossy(),
+				to.to_string_lossy(),
+			),
 			ErrorKind::FailedToRenderTemplate(name) => {
 				write!(f, "Failed to render template '{}'.", name)
 			}
 			ErrorKind::FailedToWrite(path) => {
 				write!(f, "Failed to write '{}'.", path.to_string_lossy())
 			}
+			ErrorKind::InvalidGlob(pattern) => write!(f, "Invalid glob '{}'.", pattern),
 			ErrorKind::Message(message) => write!(f, "{}", message),
 			ErrorKind::PathHasInvalidFileName(path) => {
 				write!(f, "Path '{}' has invalid filename.", path.to_string_lossy())
@@ -0,0 +1,34 @@
+use crate::project_data::Project;
+use crate::target_code_generators::{executable, x11_executable, TargetCodeGenerator};
+use crate::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "ExecutableTargetCodeGeneratorSettings")]
+#[serde(rename_all = "kebab-case", tag = "tool")]
+pub enum Settings {
+	Windows,
+	X11,
+}
+
+impl Settings {
+	pub fn instantiate<'a>(
+		&'a self,
+		project: &'a Project,
+	) -> Result<Box<(dyn TargetCodeGenerator + 'a)>> {
+		let instance: Box<(dyn TargetCodeGenerator + 'a)> = match self {
+			Settings::Windows => Box::new(executable::ExecutableTargetCodeGenerator::new(project)?),
+			Settings::X11 => Box::new(x11_executable::X11ExecutableTargetCodeGenerator::new(
+				project,
+			)?),
+		};
+		Ok(instance)
+	}
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Settings::Windows
+	}
+}
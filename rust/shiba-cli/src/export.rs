@@ -69,12 +69,23 @@ pub fn export(options: &ExportOptions) -> Result<PathBuf> {
 	fs::copy(&options.build_path, &copy_to)
 		.map_err(|err| Error::failed_to_copy(&options.build_path, &copy_to, err))?;
 
+	// Static files keep their location relative to the project directory.
 	for static_file in options.static_files {
-		if let Some(file_name) = static_file.file_name() {
-			let copy_to = temp_named_directory.join(&file_name);
-			fs::copy(&static_file, &copy_to)
-				.map_err(|err| Error::failed_to_copy(&static_file, &copy_to, err))?;
+		let relative_path = match static_file.strip_prefix(&options.project.directory) {
+			Ok(relative_path) => relative_path,
+			Err(_) => match static_file.file_name() {
+				Some(file_name) => Path::new(file_name),
+				None => continue,
+			},
+		};
+
+		let copy_to = temp_named_directory.join(relative_path);
+		if let Some(parent) = copy_to.parent() {
+			fs::create_dir_all(parent)
+				.map_err(|err| Error::failed_to_create_directory(parent, err))?;
 		}
+		fs::copy(&static_file, &copy_to)
+			.map_err(|err| Error::failed_to_copy(&static_file, &copy_to, err))?;
 	}
 
 	let output_path = match options.output {
@@ -0,0 +1,104 @@
+use crate::compilation::{Platform, System};
+use crate::configuration::Configuration;
+use crate::hash_extra::{self, ToolIdentity};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Driver {
+	Clang,
+	Gcc,
+	Mingw,
+}
+
+impl Driver {
+	pub fn get_configuration_name(self, platform: Platform) -> &'static str {
+		match (self, platform) {
+			(Driver::Clang, _) => "clang++",
+			(Driver::Gcc, _) => "g++",
+			(Driver::Mingw, Platform::X64) => "x86_64-w64-mingw32-g++",
+			(Driver::Mingw, Platform::X86) => "i686-w64-mingw32-g++",
+		}
+	}
+
+	/// Returns the configuration names of the drivers for all platforms.
+	pub fn get_configuration_names(self) -> Vec<&'static str> {
+		let mut names = vec![
+			self.get_configuration_name(Platform::X64),
+			self.get_configuration_name(Platform::X86),
+		];
+		names.dedup();
+		names
+	}
+
+	pub fn get_system(self) -> System {
+		match self {
+			Driver::Clang | Driver::Gcc => System::Linux,
+			Driver::Mingw => System::Windows,
+		}
+	}
+}
+
+#[derive(Serialize)]
+pub struct CommandGeneratorInputs<'a> {
+	pub driver: Driver,
+	pub tools: &'a BTreeMap<Platform, ToolIdentity>,
+}
+
+pub struct CommandGenerator {
+	driver: Driver,
+	paths: BTreeMap<Platform, PathBuf>,
+	tools: BTreeMap<Platform, ToolIdentity>,
+}
+
+impl CommandGenerator {
+	pub fn new(configuration: &Configuration, driver: Driver) -> Self {
+		let paths = [Platform::X64, Platform::X86]
+			.iter()
+			.map(|&platform| {
+				(
+					platform,
+					configuration.get_path(driver.get_configuration_name(platform)),
+				)
+			})
+			.collect::<BTreeMap<_, _>>();
+		let tools = paths
+			.iter()
+			.map(|(&platform, path)| (platform, hash_extra::get_tool_identity(path)))
+			.collect();
+
+		CommandGenerator {
+			driver,
+			paths,
+			tools,
+		}
+	}
+
+	pub fn get_inputs(&self) -> CommandGeneratorInputs {
+		CommandGeneratorInputs {
+			driver: self.driver,
+			tools: &self.tools,
+		}
+	}
+
+	pub fn get_path(&self, platform: Platform) -> &Path {
+		&self.paths[&platform]
+	}
+
+	pub fn command(&self, platform: Platform) -> Command {
+		let mut command = Command::new(self.get_path(platform));
+
+		// Mingw toolchains are dedicated to one platform.
+		if self.driver != Driver::Mingw {
+			command.arg(match platform {
+				Platform::X64 => "-m64",
+				Platform::X86 => "-m32",
+			});
+		}
+
+		command
+	}
+}
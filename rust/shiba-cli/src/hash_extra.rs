@@ -1,19 +1,75 @@
-use crate::paths::BUILD_CACHE_DIRECTORY;
-use crate::{Error, Result};
-use std::collections::hash_map::DefaultHasher;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
 use std::fs;
-use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
 
-pub fn compute_hash(value: &impl Hash) -> u64 {
-	let mut hasher = DefaultHasher::new();
-	value.hash(&mut hasher);
-	hasher.finish()
+pub fn compute_digest(bytes: &[u8]) -> String {
+	format!("{:x}", Sha256::digest(bytes))
 }
 
-pub fn get_build_cache_directory(value: &impl Hash) -> Result<PathBuf> {
-	let hash = compute_hash(value);
-	let path = BUILD_CACHE_DIRECTORY.join(format!("{:x}", hash));
-	fs::create_dir_all(&path).map_err(|err| Error::failed_to_create_directory(&path, err))?;
-	Ok(path)
+/// Identifies a tool by the digest of its executable when it can be found, by its path otherwise.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolIdentity {
+	Digest(String),
+	Path(PathBuf),
+}
+
+/// Returns the path of the executable, looking it up in the PATH if it is a bare name.
+pub fn find_executable(path: &Path) -> Option<PathBuf> {
+	if path.components().count() != 1 {
+		return Some(path.to_path_buf()).filter(|path| path.is_file());
+	}
+
+	let mut names = vec![path.as_os_str().to_os_string()];
+	if cfg!(windows) {
+		let mut name = names[0].clone();
+		name.push(".exe");
+		names.push(name);
+	}
+
+	let paths = env::var_os("PATH")?;
+	env::split_paths(&paths).find_map(|directory| {
+		names
+			.iter()
+			.map(|name| directory.join(name))
+			.find(|candidate| candidate.is_file())
+	})
+}
+
+pub fn get_tool_identity(path: &Path) -> ToolIdentity {
+	lazy_static! {
+		static ref DIGESTS: Mutex<HashMap<PathBuf, (SystemTime, String)>> =
+			Mutex::new(HashMap::new());
+	}
+
+	let executable_path = match find_executable(path) {
+		Some(executable_path) => executable_path,
+		None => return ToolIdentity::Path(path.to_path_buf()),
+	};
+
+	let modified = match fs::metadata(&executable_path).and_then(|metadata| metadata.modified()) {
+		Ok(modified) => modified,
+		Err(_) => return ToolIdentity::Path(path.to_path_buf()),
+	};
+
+	let mut digests = DIGESTS.lock().unwrap();
+	if let Some((digest_modified, digest)) = digests.get(&executable_path) {
+		if *digest_modified == modified {
+			return ToolIdentity::Digest(digest.clone());
+		}
+	}
+
+	match fs::read(&executable_path) {
+		Ok(contents) => {
+			let digest = compute_digest(&contents);
+			digests.insert(executable_path, (modified, digest.clone()));
+			ToolIdentity::Digest(digest)
+		}
+		Err(_) => ToolIdentity::Path(path.to_path_buf()),
+	}
 }
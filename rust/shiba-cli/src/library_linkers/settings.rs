@@ -1,17 +1,51 @@
-use crate::linkers::{msvc, Linker};
+use crate::compilation::System;
+use crate::gcc::Driver;
+use crate::linkers::{ld, msvc, Linker};
 use crate::project_data::Project;
 use crate::Result;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "LibraryLinkerSettings")]
 #[serde(rename_all = "kebab-case", tag = "tool")]
 pub enum Settings {
+	Ld(ld::LdSettings),
+	Lld(ld::LdSettings),
+	Mingw(ld::LdSettings),
 	Msvc(msvc::MsvcSettings),
 }
 
 impl Settings {
+	/// Returns the names of the tools to configure.
+	pub fn get_tool_names(&self) -> Vec<&'static str> {
+		match self {
+			Settings::Ld(_) => Driver::Gcc.get_configuration_names(),
+			Settings::Lld(_) => Driver::Clang.get_configuration_names(),
+			Settings::Mingw(_) => Driver::Mingw.get_configuration_names(),
+			Settings::Msvc(_) => vec![],
+		}
+	}
+
+	/// Returns the system of the libraries linked.
+	pub fn get_system(&self) -> System {
+		match self {
+			Settings::Ld(_) => Driver::Gcc.get_system(),
+			Settings::Lld(_) => Driver::Clang.get_system(),
+			Settings::Mingw(_) => Driver::Mingw.get_system(),
+			Settings::Msvc(_) => System::Windows,
+		}
+	}
+
 	pub fn instantiate<'a>(&'a self, project: &'a Project) -> Result<Box<(dyn Linker + 'a)>> {
 		let instance: Box<(dyn Linker + 'a)> = match self {
+			Settings::Ld(settings) => Box::new(ld::LdLinker::new(project, settings, Driver::Gcc)?),
+			Settings::Lld(settings) => {
+				Box::new(ld::LdLinker::new(project, settings, Driver::Clang)?)
+			}
+			Settings::Mingw(settings) => {
+				Box::new(ld::LdLinker::new(project, settings, Driver::Mingw)?)
+			}
 			Settings::Msvc(settings) => Box::new(msvc::MsvcLinker::new(project, settings)?),
 		};
 		Ok(instance)
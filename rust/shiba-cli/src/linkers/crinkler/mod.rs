@@ -3,32 +3,35 @@ mod settings;
 pub use self::settings::CrinklerSettings;
 use super::{LinkOptions, Linker};
 use crate::build::{BuildOptions, BuildTarget};
+use crate::build_cache;
 use crate::compilation::{Platform, PlatformDependent};
-use crate::hash_extra;
+use crate::hash_extra::{self, ToolIdentity};
 use crate::msvc;
-use crate::paths::BUILD_ROOT_DIRECTORY;
 use crate::project_data::Project;
 use crate::{Error, Result};
+use serde::Serialize;
 use std::collections::BTreeSet;
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 pub struct CrinklerLinker<'a> {
 	settings: &'a CrinklerSettings,
 
 	crinkler_path: PathBuf,
+	crinkler_tool: ToolIdentity,
 	msvc_command_generator: msvc::CommandGenerator,
 }
 
 impl<'a> CrinklerLinker<'a> {
 	pub fn new(project: &'a Project, settings: &'a CrinklerSettings) -> Result<Self> {
 		let crinkler_path = project.configuration.get_path("crinkler");
+		let crinkler_tool = hash_extra::get_tool_identity(&crinkler_path);
 		let msvc_command_generator = msvc::CommandGenerator::new()?;
 
 		Ok(CrinklerLinker {
 			settings,
 
 			crinkler_path,
+			crinkler_tool,
 			msvc_command_generator,
 		})
 	}
@@ -40,30 +43,30 @@ impl<'a> Linker for CrinklerLinker<'a> {
 
 		const OUTPUT_FILENAME: &str = "crinkler.exe";
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
-			crinkler_path: &'a Path,
+			crinkler_tool: &'a ToolIdentity,
 			msvc_command_generator: msvc::CommandGeneratorInputs<'a>,
 			options: &'a LinkOptions<'a>,
 			settings: &'a CrinklerSettings,
 		}
 
 		let inputs = Inputs {
-			crinkler_path: &self.crinkler_path,
+			crinkler_tool: &self.crinkler_tool,
 			msvc_command_generator: self.msvc_command_generator.get_inputs(),
 			options,
 			settings: self.settings,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "linkers/crinkler", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			return Ok(build_cache_path);
 		}
 
-		let build_directory = BUILD_ROOT_DIRECTORY.join("linkers").join("crinkler");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let dependencies = &options.linking.common.link_dependencies;
 		let library_paths = &options.linking.common.link_library_paths;
@@ -112,8 +115,7 @@ impl<'a> Linker for CrinklerLinker<'a> {
 		}
 
 		let copy_from = build_directory.join(OUTPUT_FILENAME);
-		fs::copy(&copy_from, &build_cache_path)
-			.map_err(|err| Error::failed_to_copy(&copy_from, &build_cache_path, err))?;
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
 
 		Ok(build_cache_path)
 	}
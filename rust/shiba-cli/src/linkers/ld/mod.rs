@@ -0,0 +1,185 @@
+mod settings;
+
+pub use self::settings::LdSettings;
+use super::{LinkOptions, Linker};
+use crate::build::{BuildOptions, BuildTarget};
+use crate::build_cache;
+use crate::compilation::{Platform, PlatformDependent, System};
+use crate::gcc;
+use crate::project_data::Project;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+pub struct LdLinker<'a> {
+	settings: &'a LdSettings,
+
+	driver: gcc::Driver,
+	gcc_command_generator: gcc::CommandGenerator,
+}
+
+impl<'a> LdLinker<'a> {
+	pub fn new(
+		project: &'a Project,
+		settings: &'a LdSettings,
+		driver: gcc::Driver,
+	) -> Result<Self> {
+		let gcc_command_generator = gcc::CommandGenerator::new(&project.configuration, driver);
+
+		Ok(LdLinker {
+			settings,
+
+			driver,
+			gcc_command_generator,
+		})
+	}
+}
+
+// Bare names such as "opengl32.lib" are given to the linker as "-lopengl32", paths are kept as is.
+fn to_dependency_arg(dependency: &Path) -> String {
+	if dependency.components().count() != 1 {
+		return dependency.to_string_lossy().to_string();
+	}
+
+	let name = match dependency.extension() {
+		Some(extension) if extension == "lib" => dependency.file_stem().unwrap(),
+		_ => dependency.as_os_str(),
+	};
+	format!("-l{}", name.to_string_lossy())
+}
+
+impl<'a> Linker for LdLinker<'a> {
+	fn link(&self, build_options: &BuildOptions, options: &LinkOptions) -> Result<PathBuf> {
+		let system = self.driver.get_system();
+
+		let output_filename = match (system, build_options.target) {
+			(System::Linux, BuildTarget::Executable) => "ld",
+			(System::Linux, BuildTarget::Library) => "ld.so",
+			(System::Windows, BuildTarget::Executable) => "ld.exe",
+			(System::Windows, BuildTarget::Library) => "ld.dll",
+		};
+
+		#[derive(Serialize)]
+		struct Inputs<'a> {
+			gcc_command_generator: gcc::CommandGeneratorInputs<'a>,
+			options: &'a LinkOptions<'a>,
+			settings: &'a LdSettings,
+			target: BuildTarget,
+		}
+
+		let inputs = Inputs {
+			gcc_command_generator: self.gcc_command_generator.get_inputs(),
+			options,
+			settings: self.settings,
+			target: build_options.target,
+		};
+		let build_cache_entry = build_cache::lock_entry(build_options, "linkers/ld", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
+		let build_cache_path = build_cache_directory.join(output_filename);
+
+		if build_cache_entry.is_hit(&build_cache_path) {
+			return Ok(build_cache_path);
+		}
+
+		let build_directory = build_cache_entry.create_scratch_directory()?;
+
+		let dependencies = &options.linking.common.link_dependencies;
+		let library_paths = &options.linking.common.link_library_paths;
+
+		let mut command = self.gcc_command_generator.command(options.platform);
+		if self.driver == gcc::Driver::Clang {
+			command.arg("-fuse-ld=lld");
+		}
+		if build_options.target == BuildTarget::Library {
+			command.arg("-shared");
+		}
+		if system == System::Windows {
+			// Do not depend on the toolchain runtime DLLs.
+			command.arg("-static");
+		}
+		command
+			.arg("-o")
+			.arg(output_filename)
+			.args(&self.settings.args)
+			.args(
+				options
+					.linking
+					.sources
+					.iter()
+					.map(|source| source.to_string_lossy().to_string()),
+			)
+			.args(
+				self.settings
+					.library_paths
+					.iter()
+					.map(|path| format!("-L{}", path.to_string_lossy())),
+			)
+			.args(
+				library_paths
+					.iter()
+					.map(|path| format!("-L{}", path.to_string_lossy())),
+			)
+			.args(
+				self.settings
+					.dependencies
+					.iter()
+					.map(|dependency| to_dependency_arg(Path::new(dependency))),
+			)
+			.args(
+				dependencies
+					.iter()
+					.map(|dependency| to_dependency_arg(dependency)),
+			);
+		if system == System::Windows {
+			command.args(vec!["-lgdi32", "-luser32"]);
+		}
+		command.current_dir(&build_directory);
+
+		let linker_path = self.gcc_command_generator.get_path(options.platform);
+
+		let mut linking = command
+			.spawn()
+			.map_err(|err| Error::failed_to_execute(linker_path, err))?;
+
+		let status = linking.wait().unwrap();
+		if !status.success() {
+			return Err(Error::execution_failed(linker_path));
+		}
+
+		let copy_from = build_directory.join(output_filename);
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
+
+		Ok(build_cache_path)
+	}
+}
+
+impl PlatformDependent for LdLinker<'_> {
+	fn get_possible_platforms(&self) -> &'static BTreeSet<Platform> {
+		lazy_static! {
+			pub static ref POSSIBLE_PLATFORMS: BTreeSet<Platform> = {
+				let mut possible_platforms = BTreeSet::new();
+				possible_platforms.insert(Platform::X64);
+				possible_platforms.insert(Platform::X86);
+				possible_platforms
+			};
+		}
+
+		&*POSSIBLE_PLATFORMS
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_dependency_arg() {
+		assert_eq!(to_dependency_arg(Path::new("opengl32.lib")), "-lopengl32");
+		assert_eq!(to_dependency_arg(Path::new("GL")), "-lGL");
+		assert_eq!(
+			to_dependency_arg(Path::new("/usr/lib/libfoo.a")),
+			"/usr/lib/libfoo.a"
+		);
+	}
+}
@@ -0,0 +1,15 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LdSettings {
+	#[serde(default)]
+	pub args: Vec<String>,
+	#[serde(default)]
+	pub dependencies: BTreeSet<String>,
+	#[serde(default)]
+	pub library_paths: BTreeSet<PathBuf>,
+}
@@ -1,13 +1,15 @@
 pub mod crinkler;
+pub mod ld;
 pub mod msvc;
 
 use crate::build::BuildOptions;
 use crate::compilation::{Platform, PlatformDependent};
 use crate::compilation_data::Linking;
 use crate::Result;
+use serde::Serialize;
 use std::path::PathBuf;
 
-#[derive(Hash)]
+#[derive(Serialize)]
 pub struct LinkOptions<'a> {
 	pub linking: &'a Linking,
 	pub platform: Platform,
@@ -3,14 +3,13 @@ mod settings;
 pub use self::settings::MsvcSettings;
 use super::{LinkOptions, Linker};
 use crate::build::{BuildOptions, BuildTarget};
+use crate::build_cache;
 use crate::compilation::{Platform, PlatformDependent};
-use crate::hash_extra;
 use crate::msvc;
-use crate::paths::BUILD_ROOT_DIRECTORY;
 use crate::project_data::Project;
 use crate::{Error, Result};
+use serde::Serialize;
 use std::collections::BTreeSet;
-use std::fs;
 use std::path::PathBuf;
 
 pub struct MsvcLinker<'a> {
@@ -38,7 +37,7 @@ impl<'a> Linker for MsvcLinker<'a> {
 			BuildTarget::Library => "msvc.dll",
 		};
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
 			msvc_command_generator: msvc::CommandGeneratorInputs<'a>,
 			options: &'a LinkOptions<'a>,
@@ -52,16 +51,15 @@ impl<'a> Linker for MsvcLinker<'a> {
 			settings: self.settings,
 			target: build_options.target,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry = build_cache::lock_entry(build_options, "linkers/msvc", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(output_filename);
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			return Ok(build_cache_path);
 		}
 
-		let build_directory = BUILD_ROOT_DIRECTORY.join("linkers").join("msvc");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let dependencies = &options.linking.common.link_dependencies;
 		let library_paths = &options.linking.common.link_library_paths;
@@ -112,8 +110,7 @@ impl<'a> Linker for MsvcLinker<'a> {
 		}
 
 		let copy_from = build_directory.join(output_filename);
-		fs::copy(&copy_from, &build_cache_path)
-			.map_err(|err| Error::failed_to_copy(&copy_from, &build_cache_path, err))?;
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
 
 		Ok(build_cache_path)
 	}
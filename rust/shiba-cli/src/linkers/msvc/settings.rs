@@ -1,8 +1,10 @@
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use std::path::PathBuf;
 
-#[derive(Debug, Default, Deserialize, Hash)]
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "MsvcLinkerSettings")]
 #[serde(rename_all = "kebab-case")]
 pub struct MsvcSettings {
 	#[serde(default)]
@@ -33,12 +33,22 @@ macro_rules! template_enum {
 mod asm_compilers;
 mod audio_synthesizers;
 mod build;
+mod build_cache;
 mod commands {
 	pub mod build;
+	pub mod cache;
 	pub mod clean;
+	pub mod config;
+	pub mod doctor;
 	pub mod export;
+	pub mod info;
+	pub mod init;
+	pub mod migrate;
 	pub mod run;
+	pub mod schema;
 	pub mod server;
+	pub mod validate;
+	pub mod watch;
 }
 mod compilation;
 mod compilation_data;
@@ -47,7 +57,9 @@ mod configuration;
 mod cpp_compilers;
 mod errors;
 mod executable_linkers;
+mod executable_target_code_generators;
 mod export;
+mod gcc;
 mod hash_extra;
 mod library_linkers;
 mod linkers;
@@ -56,8 +68,11 @@ mod parsers;
 mod paths;
 mod project_data;
 mod project_files;
+mod project_templates;
 mod run;
 mod settings;
+mod settings_migration;
+mod settings_validation;
 mod shader_codes;
 mod shader_data;
 mod shader_minifiers;
@@ -65,6 +80,7 @@ mod shader_providers;
 mod target_code_generators;
 
 use crate::build::BuildTarget;
+use crate::compilation::Platform;
 pub use crate::errors::{Error, Result};
 use crate::export::ExportOutput;
 use std::error::Error as StdError;
@@ -77,26 +93,109 @@ use structopt::StructOpt;
 enum Command {
 	/// Builds the project.
 	Build {
+		/// Shows for each stage whether the cache was used, and which inputs changed.
+		#[structopt(long)]
+		explain: bool,
 		#[structopt(short, long)]
 		force: bool,
+		/// Maximum number of compile jobs running at once, defaults to the number of CPUs.
+		#[structopt(short, long)]
+		jobs: Option<usize>,
+		/// Platform to build for, can be repeated to build several platforms.
+		#[structopt(long = "platform")]
+		platforms: Vec<Platform>,
+		/// Profile of the settings to use.
+		#[structopt(long)]
+		profile: Option<String>,
 		#[structopt(short, long, default_value = "executable")]
 		target: BuildTarget,
+		/// Writes a report of stage timings, as HTML if the extension is .html, as JSON otherwise.
+		#[structopt(long)]
+		timings: Option<PathBuf>,
+		/// Overrides a template variable, e.g. -D quality=2, can be repeated.
+		#[structopt(short = "D", long = "define", parse(try_from_str = settings::parse_variable))]
+		variables: Vec<(String, serde_json::Value)>,
 	},
+	/// Inspects and prunes the build cache.
+	Cache(CacheCommand),
 	/// Removes build artifacts, build cache.
 	Clean,
+	/// Inspects the configuration of tool paths.
+	Config(ConfigCommand),
+	/// Checks that the tools used to build the project can be found and executed.
+	Doctor {
+		/// Profile of the settings to use.
+		#[structopt(long)]
+		profile: Option<String>,
+		#[structopt(short, long, default_value = "executable")]
+		target: BuildTarget,
+	},
 	/// Builds and exports the project.
 	Export {
 		#[structopt(short, long, default_value = "export")]
 		export_directory: PathBuf,
 		#[structopt(short, long)]
 		force: bool,
+		/// Maximum number of compile jobs running at once, defaults to the number of CPUs.
+		#[structopt(short, long)]
+		jobs: Option<usize>,
 		#[structopt(short, long, default_value = "directory")]
 		output: ExportOutput,
+		/// Platform to export, can be repeated to export several platforms, each in its own subdirectory.
+		#[structopt(long = "platform")]
+		platforms: Vec<Platform>,
+		/// Profile of the settings to use.
+		#[structopt(long)]
+		profile: Option<String>,
 		#[structopt(short, long, default_value = "executable")]
 		target: BuildTarget,
+		/// Overrides a template variable, e.g. -D quality=2, can be repeated.
+		#[structopt(short = "D", long = "define", parse(try_from_str = settings::parse_variable))]
+		variables: Vec<(String, serde_json::Value)>,
 	},
+	/// Shows information about the project. The shaders are provided as in a build, filling the build cache.
+	Info {
+		/// Prints the information as JSON.
+		#[structopt(long)]
+		json: bool,
+		/// Profile of the settings to use.
+		#[structopt(long)]
+		profile: Option<String>,
+		#[structopt(short, long, default_value = "executable")]
+		target: BuildTarget,
+	},
+	/// Creates a project from a template.
+	Init {
+		/// Overwrites existing files.
+		#[structopt(long)]
+		force: bool,
+		/// Lists the available templates.
+		#[structopt(short, long)]
+		list: bool,
+		/// Name of the project, defaults to the name of the project directory.
+		#[structopt(long)]
+		name: Option<String>,
+		/// Built-in (empty, raymarching, shadertoy) or local template.
+		#[structopt(short, long, default_value = "empty")]
+		template: String,
+	},
+	/// Rewrites shiba.yml and the settings it extends into the current layout.
+	Migrate,
 	/// Builds and executes the project (default).
-	Run,
+	Run {
+		/// Profile of the settings to use.
+		#[structopt(long)]
+		profile: Option<String>,
+		/// Overrides a template variable, e.g. -D quality=2, can be repeated.
+		#[structopt(short = "D", long = "define", parse(try_from_str = settings::parse_variable))]
+		variables: Vec<(String, serde_json::Value)>,
+	},
+	/// Prints the JSON Schema of shiba.yml, for editor autocompletion.
+	Schema {
+		/// Writes the schema to this file instead.
+		#[structopt(short, long)]
+		output: Option<PathBuf>,
+	},
 	/// Starts a server.
 	Server {
 		#[structopt(short, long, default_value = "0.3")]
@@ -106,11 +205,57 @@ enum Command {
 		#[structopt(short, long, default_value = "5184")]
 		port: u16,
 	},
+	/// Reports unknown keys and invalid values in shiba.yml and the settings it extends.
+	Validate,
+	/// Builds the project each time its files change.
+	Watch {
+		#[structopt(short, long, default_value = "0.3")]
+		debounce_delay: f32,
+		/// Profile of the settings to use.
+		#[structopt(long)]
+		profile: Option<String>,
+		/// Relaunches the executable after each successful build.
+		#[structopt(short, long)]
+		run: bool,
+		#[structopt(short, long, default_value = "executable")]
+		target: BuildTarget,
+		/// Overrides a template variable, e.g. -D quality=2, can be repeated.
+		#[structopt(short = "D", long = "define", parse(try_from_str = settings::parse_variable))]
+		variables: Vec<(String, serde_json::Value)>,
+	},
+}
+
+#[derive(Debug, StructOpt)]
+enum CacheCommand {
+	/// Lists build cache entries, least recently used first.
+	List {
+		/// Only lists entries used by the current project.
+		#[structopt(short, long)]
+		current_project: bool,
+	},
+	/// Removes build cache entries by age, then least recently used ones by total size.
+	Prune {
+		/// Removes entries not used for this number of days.
+		#[structopt(long)]
+		max_age: Option<f32>,
+		/// Removes least recently used entries until the cache fits in this size, in MiB.
+		#[structopt(long)]
+		max_size: Option<f32>,
+	},
+}
+
+#[derive(Debug, StructOpt)]
+enum ConfigCommand {
+	/// Lists the effective tool paths, and where they come from.
+	List,
 }
 
 impl Default for Command {
 	fn default() -> Self {
-		Command::Run
+		Command::Run {
+			profile: None,
+			variables: vec![],
+		}
 	}
 }
 
@@ -122,6 +267,14 @@ struct Args {
 
 	#[structopt(short, long, default_value = ".")]
 	project_directory: PathBuf,
+
+	/// Overrides the path of a tool, e.g. --tool nasm=C:/nasm/nasm.exe, can be repeated.
+	#[structopt(
+		long = "tool",
+		number_of_values = 1,
+		parse(try_from_str = configuration::parse_tool_override)
+	)]
+	tool_overrides: Vec<(String, PathBuf)>,
 }
 
 fn run() -> Result<()> {
@@ -129,34 +282,125 @@ fn run() -> Result<()> {
 
 	let command = args.command.unwrap_or_default();
 	match command {
-		Command::Build { force, target } => commands::build::execute(&commands::build::Options {
+		Command::Build {
+			explain,
+			force,
+			jobs,
+			platforms,
+			profile,
+			target,
+			timings,
+			variables,
+		} => commands::build::execute(&commands::build::Options {
+			explain,
 			force,
+			jobs,
+			platforms: &platforms,
+			profile: profile.as_deref(),
 			project_directory: &args.project_directory,
 			target,
+			timings: timings.as_deref(),
+			tool_overrides: &args.tool_overrides,
+			variables: &variables,
 		})
 		.map(|_| ()),
 
+		Command::Cache(CacheCommand::List { current_project }) => {
+			commands::cache::list(&commands::cache::ListOptions {
+				current_project,
+				project_directory: &args.project_directory,
+			})
+		}
+
+		Command::Cache(CacheCommand::Prune { max_age, max_size }) => {
+			commands::cache::prune(&commands::cache::PruneOptions {
+				max_age_days: max_age,
+				max_size_mib: max_size,
+			})
+		}
+
 		Command::Clean => commands::clean::execute().map(|_| ()),
 
+		Command::Config(ConfigCommand::List) => {
+			commands::config::list(&commands::config::ListOptions {
+				project_directory: &args.project_directory,
+				tool_overrides: &args.tool_overrides,
+			})
+		}
+
+		Command::Doctor { profile, target } => {
+			commands::doctor::execute(&commands::doctor::Options {
+				profile: profile.as_deref(),
+				project_directory: &args.project_directory,
+				target,
+				tool_overrides: &args.tool_overrides,
+			})
+		}
+
 		Command::Export {
 			export_directory,
 			force,
+			jobs,
 			output,
+			platforms,
+			profile,
 			target,
+			variables,
 		} => commands::export::execute(&commands::export::Options {
 			export_directory: &export_directory,
 			force,
+			jobs,
 			output,
+			platforms: &platforms,
+			profile: profile.as_deref(),
 			project_directory: &args.project_directory,
 			target,
+			tool_overrides: &args.tool_overrides,
+			variables: &variables,
 		})
 		.map(|_| ()),
 
-		Command::Run => commands::run::execute(&commands::run::Options {
+		Command::Info {
+			json,
+			profile,
+			target,
+		} => commands::info::execute(&commands::info::Options {
+			json,
+			profile: profile.as_deref(),
 			project_directory: &args.project_directory,
+			target,
+			tool_overrides: &args.tool_overrides,
+		}),
+
+		Command::Init {
+			force,
+			list,
+			name,
+			template,
+		} => commands::init::execute(&commands::init::Options {
+			force,
+			list,
+			name: name.as_deref(),
+			project_directory: &args.project_directory,
+			template: &template,
+		}),
+
+		Command::Migrate => commands::migrate::execute(&commands::migrate::Options {
+			project_directory: &args.project_directory,
+		}),
+
+		Command::Run { profile, variables } => commands::run::execute(&commands::run::Options {
+			profile: profile.as_deref(),
+			project_directory: &args.project_directory,
+			tool_overrides: &args.tool_overrides,
+			variables: &variables,
 		})
 		.map(|_| ()),
 
+		Command::Schema { output } => commands::schema::execute(&commands::schema::Options {
+			output: output.as_deref(),
+		}),
+
 		Command::Server {
 			debounce_delay,
 			ip,
@@ -166,6 +410,27 @@ fn run() -> Result<()> {
 			ip,
 			port,
 			project_directory: &args.project_directory,
+			tool_overrides: &args.tool_overrides,
+		}),
+
+		Command::Validate => commands::validate::execute(&commands::validate::Options {
+			project_directory: &args.project_directory,
+		}),
+
+		Command::Watch {
+			debounce_delay,
+			profile,
+			run,
+			target,
+			variables,
+		} => commands::watch::execute(&commands::watch::Options {
+			debounce_delay: Duration::from_secs_f32(debounce_delay),
+			profile: profile.as_deref(),
+			project_directory: &args.project_directory,
+			run,
+			target,
+			tool_overrides: &args.tool_overrides,
+			variables: &variables,
 		}),
 	}
 }
@@ -2,7 +2,7 @@ use crate::compilation::Platform;
 use crate::{Error, Result};
 use encoding::all::UTF_8;
 use encoding::{DecoderTrap, Encoding};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::process::Command;
 use std::str;
@@ -15,19 +15,21 @@ fn default_true() -> bool {
 #[serde(rename_all = "camelCase")]
 struct VSWhereItem {
 	pub installation_path: String,
+	pub installation_version: String,
 	#[serde(default = "default_true")]
 	pub is_complete: bool,
 	#[serde(default = "default_true")]
 	pub is_launchable: bool,
 }
 
-#[derive(Hash)]
+#[derive(Serialize)]
 pub struct CommandGeneratorInputs<'a> {
-	pub installation_path: &'a String,
+	pub installation_version: &'a String,
 }
 
 pub struct CommandGenerator {
 	installation_path: String,
+	installation_version: String,
 }
 
 impl CommandGenerator {
@@ -43,19 +45,20 @@ impl CommandGenerator {
 			.map_err(|err| Error::failed_to_convert_utf8(&vswhere.stdout, err))?;
 		let items: Vec<VSWhereItem> =
 			serde_json::from_str(&json).map_err(|err| Error::failed_to_deserialize(&json, err))?;
-		let installation_path = items
+		let item = items
 			.iter()
 			.find(|&item| item.is_complete && item.is_launchable)
-			.ok_or("Cannot find any VS installation.")?
-			.installation_path
-			.clone();
+			.ok_or("Cannot find any VS installation.")?;
 
-		Ok(CommandGenerator { installation_path })
+		Ok(CommandGenerator {
+			installation_path: item.installation_path.clone(),
+			installation_version: item.installation_version.clone(),
+		})
 	}
 
 	pub fn get_inputs(&self) -> CommandGeneratorInputs {
 		CommandGeneratorInputs {
-			installation_path: &self.installation_path,
+			installation_version: &self.installation_version,
 		}
 	}
 
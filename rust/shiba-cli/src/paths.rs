@@ -17,6 +17,11 @@ lazy_static! {
 		fs::create_dir_all(&p).unwrap();
 		p
 	};
+	pub static ref LAST_BUILDS_DIRECTORY: PathBuf = {
+		let p = TEMP_DIRECTORY.join("last-builds");
+		fs::create_dir_all(&p).unwrap();
+		p
+	};
 	pub static ref LOCAL_DATA_DIRECTORY: PathBuf = {
 		let p = dirs::data_local_dir().unwrap().join("shiba");
 		fs::create_dir_all(&p).unwrap();
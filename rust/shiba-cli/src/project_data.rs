@@ -1,21 +1,48 @@
 use crate::build::BuildTarget;
+use crate::compilation::Platform;
 use crate::configuration::Configuration;
-use crate::settings::Settings;
+use crate::settings::{RuntimeSettings, Settings, Variables};
 use crate::Result;
+use serde::Serialize;
+use std::env;
 use std::path::{Path, PathBuf};
 
 pub struct Project {
 	pub configuration: Configuration,
 	pub development: bool,
 	pub directory: PathBuf,
+	pub profile: Option<String>,
 	pub settings: Settings,
 }
 
+/// Context of shader and code templates.
+#[derive(Serialize)]
+pub struct TemplateContext<'a> {
+	pub development: bool,
+	pub runtime_settings: &'a RuntimeSettings,
+	pub target: BuildTarget,
+	pub variables: &'a Variables,
+}
+
 impl<'a> Project {
-	pub fn load(directory: &'a Path, target: BuildTarget) -> Result<Self> {
-		let configuration = Configuration::load()?;
+	pub fn load(
+		directory: &'a Path,
+		target: BuildTarget,
+		profile: Option<&str>,
+		platform: Option<Platform>,
+		tool_overrides: &[(String, PathBuf)],
+	) -> Result<Self> {
+		// Tools run in their own directories, so paths to project files must be absolute.
+		let directory = if directory.is_absolute() {
+			directory.to_path_buf()
+		} else {
+			env::current_dir()
+				.map(|current_directory| current_directory.join(directory))
+				.unwrap_or_else(|_| directory.to_path_buf())
+		};
 
-		let settings = Settings::load(directory)?;
+		let configuration = Configuration::load(&directory, tool_overrides)?;
+		let settings = Settings::load(&directory, profile, platform)?;
 
 		let development = match settings.development {
 			Some(development) => development,
@@ -28,8 +55,37 @@ impl<'a> Project {
 		Ok(Project {
 			configuration,
 			development,
-			directory: PathBuf::from(directory),
+			directory,
+			profile: profile.map(|profile| profile.to_string()),
 			settings,
 		})
 	}
+
+	/// Returns the project directory, then the directories of the projects extended, nearest first.
+	pub fn get_directories(&self) -> impl Iterator<Item = &Path> {
+		std::iter::once(self.directory.as_path()).chain(
+			self.settings
+				.base_directories
+				.iter()
+				.map(|directory| directory.as_path()),
+		)
+	}
+
+	/// Returns the path of the file in the first directory containing it, or in the project directory otherwise.
+	pub fn find_file(&self, relative_path: impl AsRef<Path>) -> PathBuf {
+		let relative_path = relative_path.as_ref();
+		self.get_directories()
+			.map(|directory| directory.join(relative_path))
+			.find(|path| path.exists())
+			.unwrap_or_else(|| self.directory.join(relative_path))
+	}
+
+	pub fn get_template_context(&self, target: BuildTarget) -> TemplateContext<'_> {
+		TemplateContext {
+			development: self.development,
+			runtime_settings: &self.settings.runtime,
+			target,
+			variables: &self.settings.variables,
+		}
+	}
 }
@@ -1,11 +1,18 @@
-use crate::build::BuildTarget;
+use crate::compilation::Platform;
+use crate::hash_extra;
+use crate::project_data::TemplateContext;
+use crate::settings;
 use crate::{Error, Result};
-use serde::Serialize;
-use std::collections::BTreeMap;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tera::{Context, Tera};
 
+/// Name of the files listing paths to ignore, with the gitignore syntax.
+pub const IGNORE_FILENAME: &str = ".shibaignore";
+
 pub type IsPathHandled<'a> = Box<dyn Fn(&Path) -> bool + 'a>;
 
 pub trait FileConsumer {
@@ -17,65 +24,240 @@ pub type CodeMap = BTreeMap<String, String>;
 #[derive(Debug)]
 pub struct ProjectFiles {
 	code_files: Vec<PathBuf>,
+	header_files: Vec<PathBuf>,
+	prebuilt_files: Vec<PathBuf>,
+	project_directory: PathBuf,
+	source_files: Vec<PathBuf>,
 	static_files: Vec<PathBuf>,
 }
 
 pub struct LoadOptions<'a> {
+	/// Directories of the projects extended, whose code files are used when the project does not override them.
+	pub base_directories: &'a [PathBuf],
 	pub compiler_paths: &'a [IsPathHandled<'a>],
+	pub exclude: &'a [String],
 	pub ignore_paths: &'a [IsPathHandled<'a>],
+	pub include: &'a [String],
+	pub sources: &'a [String],
+}
+
+// Like in .shibaignore files, "*" does not match across directories, whereas "**" does.
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+	let mut builder = GlobSetBuilder::new();
+	for pattern in patterns {
+		let glob = GlobBuilder::new(pattern)
+			.literal_separator(true)
+			.build()
+			.map_err(|err| Error::invalid_glob(pattern, err))?;
+		builder.add(glob);
+	}
+	builder
+		.build()
+		.map_err(|err| Error::invalid_glob(&patterns.join(", "), err))
+}
+
+// Exports go in the project directory by default, they must not be exported again.
+const EXPORT_DIRECTORY_GLOB: &str = "export";
+
+/// Same as build_glob_set, the export directory being always excluded.
+pub fn build_exclude_glob_set(patterns: &[String]) -> Result<GlobSet> {
+	let mut patterns = patterns.to_vec();
+	patterns.push(EXPORT_DIRECTORY_GLOB.to_string());
+	build_glob_set(&patterns)
+}
+
+/// Walks the directory recursively, returning included files. Hidden entries, entries listed in .shibaignore files and excluded entries are skipped.
+pub fn walk_files(directory: &Path, include: &GlobSet, exclude: &GlobSet) -> Result<Vec<PathBuf>> {
+	let root = directory.to_path_buf();
+	let exclude = exclude.clone();
+	let walker = WalkBuilder::new(directory)
+		.standard_filters(false)
+		.hidden(true)
+		.add_custom_ignore_filename(IGNORE_FILENAME)
+		.sort_by_file_path(|a, b| a.cmp(b))
+		.filter_entry(move |entry| match entry.path().strip_prefix(&root) {
+			Ok(relative_path) => {
+				relative_path.as_os_str().is_empty() || !exclude.is_match(relative_path)
+			}
+			Err(_) => true,
+		})
+		.build();
+
+	let mut paths = vec![];
+	for entry in walker {
+		let entry = entry.map_err(|err| {
+			Error::message(format!(
+				"Failed to walk directory '{}': {}",
+				directory.to_string_lossy(),
+				err
+			))
+		})?;
+
+		if !matches!(entry.file_type(), Some(file_type) if file_type.is_file()) {
+			continue;
+		}
+
+		let path = entry.into_path();
+
+		let relative_path = path.strip_prefix(directory).unwrap_or(&path);
+		if include.is_match(relative_path) {
+			paths.push(path);
+		}
+	}
+	Ok(paths)
+}
+
+fn get_code_name(path: &Path) -> String {
+	path.file_stem()
+		.unwrap()
+		.to_str()
+		.expect("Failed to convert path.")
+		.to_string()
 }
 
 impl ProjectFiles {
+	/// Walks the project directory, then the base directories for code files not overridden by the project.
 	pub fn load(project_directory: &Path, options: &LoadOptions) -> Result<Self> {
+		let include = build_glob_set(options.include)?;
+		let exclude = build_exclude_glob_set(options.exclude)?;
+		let sources = build_glob_set(options.sources)?;
+
 		let mut code_files = vec![];
+		let mut header_files = vec![];
+		let mut prebuilt_files = vec![];
+		let mut source_files = vec![];
 		let mut static_files = vec![];
 
-		let entries = fs::read_dir(&project_directory)
-			.map_err(|err| Error::failed_to_read_directory(&project_directory, err))?;
+		for path in walk_files(project_directory, &include, &exclude)? {
+			let relative_path = path.strip_prefix(project_directory).unwrap_or(&path);
+
+			if options.ignore_paths.iter().any(|handler| handler(&path)) {
+				// Skip.
+			} else if is_header_path(&path) {
+				header_files.push(path);
+			} else if is_object_path(&path) || is_library_path(&path) {
+				prebuilt_files.push(path);
+			} else if is_source_path(&path) && sources.is_match(relative_path) {
+				source_files.push(path);
+			} else if options.compiler_paths.iter().any(|handler| handler(&path)) {
+				code_files.push(path);
+			} else {
+				static_files.push(path);
+			}
+		}
 
-		for entry in entries {
-			let entry = entry.unwrap();
-			if entry.file_type().unwrap().is_file() {
-				let path = entry.path();
+		let mut code_names = code_files
+			.iter()
+			.map(|path| get_code_name(path))
+			.collect::<BTreeSet<_>>();
+		for base_directory in options.base_directories {
+			for path in walk_files(base_directory, &include, &exclude)? {
+				let relative_path = path.strip_prefix(base_directory).unwrap_or(&path);
+				let is_source = is_source_path(&path) && sources.is_match(relative_path);
 
-				if options.ignore_paths.iter().any(|handler| handler(&path)) {
-					// Skip.
-				} else if options.compiler_paths.iter().any(|handler| handler(&path)) {
+				if !options.ignore_paths.iter().any(|handler| handler(&path))
+					&& !is_source && options.compiler_paths.iter().any(|handler| handler(&path))
+					&& code_names.insert(get_code_name(&path))
+				{
 					code_files.push(path);
-				} else {
-					static_files.push(path);
 				}
 			}
 		}
 
 		Ok(ProjectFiles {
 			code_files,
+			header_files,
+			prebuilt_files,
+			project_directory: project_directory.to_path_buf(),
+			source_files,
 			static_files,
 		})
 	}
 
-	pub fn get_compiler_codes(&self, development: bool, target: BuildTarget) -> Result<CodeMap> {
-		#[derive(Serialize)]
-		struct OwnContext {
-			development: bool,
-			target: BuildTarget,
-		}
+	/// Returns the digests of the headers, by path.
+	pub fn get_header_digests(&self) -> Result<BTreeMap<PathBuf, String>> {
+		self.header_files
+			.iter()
+			.map(|path| {
+				let contents = fs::read(path).map_err(|err| Error::failed_to_read(path, err))?;
+				Ok((path.clone(), hash_extra::compute_digest(&contents)))
+			})
+			.collect()
+	}
 
-		let context = OwnContext {
-			development,
-			target,
-		};
+	/// Returns the project directory and the directories containing headers.
+	pub fn get_include_paths(&self) -> BTreeSet<PathBuf> {
+		let mut include_paths = self
+			.header_files
+			.iter()
+			.filter_map(|path| path.parent())
+			.map(|path| path.to_path_buf())
+			.collect::<BTreeSet<_>>();
+		include_paths.insert(self.project_directory.clone());
+		include_paths
+	}
 
-		self.code_files
+	/// Returns the prebuilt objects and libraries to link for the platform.
+	pub fn get_prebuilt_files(
+		&self,
+		platform: Platform,
+		platforms_by_glob: &BTreeMap<String, BTreeSet<Platform>>,
+	) -> Result<Vec<&Path>> {
+		let filters = platforms_by_glob
 			.iter()
-			.map(|path| {
-				let name = path
-					.file_stem()
-					.unwrap()
-					.to_str()
-					.expect("Failed to convert path.")
-					.to_string();
+			.map(|(pattern, platforms)| {
+				let glob_set = build_glob_set(std::slice::from_ref(pattern))?;
+				Ok((glob_set, platforms))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		Ok(self
+			.prebuilt_files
+			.iter()
+			.filter(|path| {
+				let relative_path = path.strip_prefix(&self.project_directory).unwrap_or(path);
+				let mut platform_sets = filters
+					.iter()
+					.filter(|(glob_set, _)| glob_set.is_match(relative_path))
+					.map(|(_, platforms)| platforms)
+					.peekable();
+				platform_sets.peek().is_none()
+					|| platform_sets.any(|platforms| platforms.contains(&platform))
+			})
+			.map(|path| path.as_path())
+			.collect())
+	}
+
+	/// Returns the files compiled as their own translation units.
+	pub fn get_source_files(&self) -> &Vec<PathBuf> {
+		&self.source_files
+	}
+
+	/// Returns code files by name, i.e. by the slot they fill.
+	pub fn get_code_paths(&self) -> Result<BTreeMap<String, &Path>> {
+		let mut paths_by_name = BTreeMap::new();
+
+		for path in &self.code_files {
+			let name = get_code_name(path);
+
+			// Codes are identified by file stem, whatever their directory.
+			if let Some(other_path) = paths_by_name.insert(name.clone(), path.as_path()) {
+				return Err(Error::message(format!(
+					"Code {} is defined by both '{}' and '{}'.",
+					name,
+					other_path.to_string_lossy(),
+					path.to_string_lossy()
+				)));
+			}
+		}
 
+		Ok(paths_by_name)
+	}
+
+	pub fn get_compiler_codes(&self, context: &TemplateContext) -> Result<CodeMap> {
+		self.get_code_paths()?
+			.into_iter()
+			.map(|(name, path)| {
 				let contents =
 					fs::read_to_string(&path).map_err(|err| Error::failed_to_read(&path, err))?;
 
@@ -87,7 +269,7 @@ impl ProjectFiles {
 				let contents = tera
 					.render(
 						&name,
-						&Context::from_serialize(&context).expect("Failed to create context."),
+						&Context::from_serialize(context).expect("Failed to create context."),
 					)
 					.map_err(|err| Error::failed_to_render_template(&name, err))?;
 
@@ -100,3 +282,198 @@ impl ProjectFiles {
 		&self.static_files
 	}
 }
+
+/// Tells whether the file is a project code, filling a slot of the target code generator.
+pub fn is_code_path(path: &Path) -> bool {
+	matches!(path.extension(), Some(extension) if extension == "cpp")
+}
+
+/// Tells whether the file is a C/C++ header, put on the include path.
+pub fn is_header_path(path: &Path) -> bool {
+	match path.extension() {
+		Some(extension) => ["h", "hh", "hpp", "inl"]
+			.iter()
+			.any(|header_extension| extension == *header_extension),
+		None => false,
+	}
+}
+
+/// Tells whether the file is a C/C++ source, compiled as its own translation unit when matched by the sources globs.
+pub fn is_source_path(path: &Path) -> bool {
+	match path.extension() {
+		Some(extension) => ["c", "cc", "cpp", "cxx"]
+			.iter()
+			.any(|source_extension| extension == *source_extension),
+		None => false,
+	}
+}
+
+/// Tells whether the file is a prebuilt object, linked as a source.
+pub fn is_object_path(path: &Path) -> bool {
+	matches!(path.extension(), Some(extension) if extension == "o" || extension == "obj")
+}
+
+/// Tells whether the file is a prebuilt static library, linked as a dependency.
+pub fn is_library_path(path: &Path) -> bool {
+	matches!(path.extension(), Some(extension) if extension == "a" || extension == "lib")
+}
+
+/// Tells whether the file is used by no tool, nor exported.
+pub fn is_ignored_path(path: &Path) -> bool {
+	match path.file_name() {
+		Some(file_name) => {
+			let file_name = file_name.to_string_lossy();
+			file_name.starts_with('.') || file_name == settings::FILENAME
+		}
+		None => false,
+	}
+}
+
+fn get_edit_distance(a: &str, b: &str) -> usize {
+	let b = b.chars().collect::<Vec<_>>();
+	let mut distances = (0..=b.len()).collect::<Vec<_>>();
+	for (i, a_char) in a.chars().enumerate() {
+		let mut previous_diagonal = distances[0];
+		distances[0] = i + 1;
+		for (j, &b_char) in b.iter().enumerate() {
+			let previous = distances[j + 1];
+			distances[j + 1] = if a_char == b_char {
+				previous_diagonal
+			} else {
+				1 + previous_diagonal.min(previous).min(distances[j])
+			};
+			previous_diagonal = previous;
+		}
+	}
+	distances[b.len()]
+}
+
+/// Checks that every code fills a slot of the target code generator, otherwise it would be silently unused.
+pub fn check_code_slots<'a>(
+	names: impl IntoIterator<Item = &'a String>,
+	slots: &[&str],
+) -> Result<()> {
+	for name in names {
+		if slots.contains(&name.as_str()) {
+			continue;
+		}
+
+		let suggestion = slots
+			.iter()
+			.map(|slot| (get_edit_distance(name, slot), slot))
+			.filter(|(distance, _)| *distance <= 3)
+			.min()
+			.map(|(_, slot)| format!(" Did you mean {}?", slot))
+			.unwrap_or_default();
+
+		return Err(Error::message(format!(
+			"Code {} does not fill any slot, expected one of: {}.{}",
+			name,
+			slots.join(", "),
+			suggestion
+		)));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::process;
+
+	#[test]
+	fn test_load() {
+		let directory =
+			std::env::temp_dir().join(format!("shiba-test-project-files-{}", process::id()));
+		let files = [
+			"assets/image.png",
+			"assets/image.psd",
+			"code/declarations.cpp",
+			"export/demo.exe",
+			"lib/math.cpp",
+			"lib/math.h",
+			"lib/notes.md",
+			"music.xrns",
+			"notes/todo.txt",
+			"player/synth.lib",
+			"player/synth_x86.obj",
+		];
+		for file in files.iter() {
+			let path = directory.join(file);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(&path, "").unwrap();
+		}
+		fs::write(directory.join(IGNORE_FILENAME), "notes/\n").unwrap();
+
+		let project_files = ProjectFiles::load(
+			&directory,
+			&LoadOptions {
+				base_directories: &[],
+				compiler_paths: &[Box::new(
+					|path| matches!(path.extension(), Some(extension) if extension == "cpp"),
+				)],
+				exclude: &["**/*.psd".to_string()],
+				ignore_paths: &[],
+				include: &["**".to_string()],
+				sources: &["lib/**".to_string()],
+			},
+		);
+
+		fs::remove_dir_all(&directory).unwrap();
+
+		let mut prebuilt_platforms = BTreeMap::new();
+		prebuilt_platforms.insert(
+			"**/*_x86.obj".to_string(),
+			vec![Platform::X86].into_iter().collect(),
+		);
+
+		let project_files = project_files.unwrap();
+		assert_eq!(
+			project_files.code_files,
+			vec![directory.join("code/declarations.cpp")]
+		);
+		assert_eq!(
+			project_files.source_files,
+			vec![directory.join("lib/math.cpp")]
+		);
+		assert_eq!(
+			project_files.header_files,
+			vec![directory.join("lib/math.h")]
+		);
+		assert_eq!(
+			project_files.static_files,
+			vec![
+				directory.join("assets/image.png"),
+				directory.join("lib/notes.md"),
+				directory.join("music.xrns"),
+			]
+		);
+
+		assert_eq!(
+			project_files
+				.get_prebuilt_files(Platform::X64, &prebuilt_platforms)
+				.unwrap(),
+			vec![directory.join("player/synth.lib")]
+		);
+		assert_eq!(
+			project_files
+				.get_prebuilt_files(Platform::X86, &prebuilt_platforms)
+				.unwrap(),
+			vec![
+				directory.join("player/synth.lib"),
+				directory.join("player/synth_x86.obj"),
+			]
+		);
+	}
+
+	#[test]
+	fn test_check_code_slots() {
+		let slots = ["declarations", "initialization", "rendering"];
+
+		assert!(check_code_slots(&["rendering".to_string()], &slots).is_ok());
+
+		let err = check_code_slots(&["initialisation".to_string()], &slots).unwrap_err();
+		assert!(err.to_string().ends_with("Did you mean initialization?"));
+	}
+}
@@ -0,0 +1 @@
+// Global variables and functions, e.g. framebuffers or textures.
@@ -0,0 +1,25 @@
+#version 450
+
+uniform float elapsedTime; // shiba time
+
+#pragma shiba attributes
+vec2 position;
+
+#pragma shiba varyings
+vec2 uv;
+
+#pragma shiba outputs
+vec4 color;
+
+#pragma shiba vertex image
+void main()
+{
+	uv = position * .5 + .5;
+	gl_Position = vec4(position, 0., 1.);
+}
+
+#pragma shiba fragment image
+void main()
+{
+	color = vec4(uv, .5 + .5 * sin(elapsedTime), 1.);
+}
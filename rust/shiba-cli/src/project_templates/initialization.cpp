@@ -0,0 +1 @@
+// Executed once, after the shaders are loaded.
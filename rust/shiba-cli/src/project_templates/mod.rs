@@ -0,0 +1,324 @@
+use crate::paths::DATA_DIRECTORY;
+use crate::project_files;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tera::{Context, Tera};
+
+/// Extension of the template files which are rendered, the extension is removed from the written file.
+const TERA_EXTENSION: &str = "tera";
+
+pub type BuiltInFiles = &'static [(&'static str, &'static str)];
+
+// Code slots filled by the target code generators, shared by the built-in templates.
+macro_rules! built_in_files {
+	($name:literal) => {
+		&[
+			("declarations.cpp", include_str!("declarations.cpp")),
+			("initialization.cpp", include_str!("initialization.cpp")),
+			("rendering.cpp", include_str!("rendering.cpp")),
+			("shader.frag", include_str!(concat!($name, "/shader.frag"))),
+			(
+				"shiba.yml.tera",
+				include_str!(concat!($name, "/shiba.yml.tera")),
+			),
+		]
+	};
+}
+
+const BUILT_IN_TEMPLATES: &[(&str, BuiltInFiles)] = &[
+	("empty", built_in_files!("empty")),
+	("raymarching", built_in_files!("raymarching")),
+	("shadertoy", built_in_files!("shadertoy")),
+];
+
+pub enum ProjectTemplate {
+	BuiltIn(BuiltInFiles),
+	/// Directory whose files are copied.
+	Local(PathBuf),
+}
+
+enum TemplateFile {
+	Contents(String),
+	/// Copied byte for byte from this path.
+	Copy(PathBuf),
+}
+
+#[derive(Serialize)]
+pub struct TemplateContext<'a> {
+	pub name: &'a str,
+	pub shiba_version: &'a str,
+}
+
+fn is_tera_path(path: &Path) -> bool {
+	path.extension() == Some(TERA_EXTENSION.as_ref())
+}
+
+/// Returns the directory where users can add their own templates, one per subdirectory.
+pub fn get_local_templates_directory() -> PathBuf {
+	DATA_DIRECTORY.join("templates")
+}
+
+/// Returns the templates by name, local templates override built-in ones.
+pub fn get_templates() -> Result<BTreeMap<String, ProjectTemplate>> {
+	let mut templates = BUILT_IN_TEMPLATES
+		.iter()
+		.map(|(name, files)| (name.to_string(), ProjectTemplate::BuiltIn(files)))
+		.collect::<BTreeMap<_, _>>();
+
+	let directory = get_local_templates_directory();
+	if directory.is_dir() {
+		let entries = fs::read_dir(&directory)
+			.map_err(|err| Error::failed_to_read_directory(&directory, err))?;
+		for entry in entries {
+			let path = entry
+				.map_err(|err| Error::failed_to_read_directory(&directory, err))?
+				.path();
+			if path.is_dir() {
+				let name = path
+					.file_name()
+					.and_then(|name| name.to_str())
+					.ok_or_else(|| Error::path_has_invalid_file_name(&path))?
+					.to_string();
+				templates.insert(name, ProjectTemplate::Local(path));
+			}
+		}
+	}
+
+	Ok(templates)
+}
+
+impl ProjectTemplate {
+	pub fn describe(&self) -> String {
+		match self {
+			ProjectTemplate::BuiltIn(_) => "built-in".to_string(),
+			ProjectTemplate::Local(directory) => directory.to_string_lossy().to_string(),
+		}
+	}
+
+	// Returns the files by relative path.
+	fn get_files(&self) -> Result<Vec<(PathBuf, TemplateFile)>> {
+		match self {
+			ProjectTemplate::BuiltIn(files) => Ok(files
+				.iter()
+				.map(|(path, contents)| {
+					(
+						PathBuf::from(path),
+						TemplateFile::Contents(contents.to_string()),
+					)
+				})
+				.collect()),
+			ProjectTemplate::Local(directory) => {
+				let include = project_files::build_glob_set(&["**".to_string()])?;
+				let exclude = project_files::build_glob_set(&[])?;
+				project_files::walk_files(directory, &include, &exclude)?
+					.into_iter()
+					.map(|path| {
+						let relative_path = path.strip_prefix(directory).unwrap_or(&path);
+						// Only templates are read, other files may be binary assets.
+						let file = if is_tera_path(relative_path) {
+							TemplateFile::Contents(
+								fs::read_to_string(&path)
+									.map_err(|err| Error::failed_to_read(&path, err))?,
+							)
+						} else {
+							TemplateFile::Copy(path.clone())
+						};
+						Ok((relative_path.to_path_buf(), file))
+					})
+					.collect()
+			}
+		}
+	}
+
+	/// Writes the files into the directory, rendering the .tera ones. Existing files are only overwritten if forced. Returns the paths written.
+	pub fn instantiate(
+		&self,
+		directory: &Path,
+		context: &TemplateContext,
+		force: bool,
+	) -> Result<Vec<PathBuf>> {
+		let files = self
+			.get_files()?
+			.into_iter()
+			.map(|(relative_path, file)| {
+				let contents = match file {
+					TemplateFile::Contents(contents) if is_tera_path(&relative_path) => contents,
+					file => return Ok((directory.join(relative_path), file)),
+				};
+
+				let name = relative_path.to_string_lossy().to_string();
+				let mut tera = Tera::default();
+				tera.add_raw_template(&name, &contents)
+					.map_err(|err| Error::failed_to_render_template(&name, err))?;
+				let contents = tera
+					.render(
+						&name,
+						&Context::from_serialize(context).expect("Failed to create context."),
+					)
+					.map_err(|err| Error::failed_to_render_template(&name, err))?;
+				Ok((
+					directory.join(relative_path.with_extension("")),
+					TemplateFile::Contents(contents),
+				))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		if !force {
+			let existing_paths = files
+				.iter()
+				.filter(|(path, _)| path.exists())
+				.map(|(path, _)| path.to_string_lossy().to_string())
+				.collect::<Vec<_>>();
+			if !existing_paths.is_empty() {
+				return Err(Error::message(format!(
+					"Files already exist: {}. Use --force to overwrite them.",
+					existing_paths.join(", ")
+				)));
+			}
+		}
+
+		for (path, file) in &files {
+			if let Some(parent) = path.parent() {
+				fs::create_dir_all(parent)
+					.map_err(|err| Error::failed_to_create_directory(parent, err))?;
+			}
+			match file {
+				TemplateFile::Contents(contents) => {
+					fs::write(path, contents).map_err(|err| Error::failed_to_write(path, err))?
+				}
+				TemplateFile::Copy(from) => {
+					fs::copy(from, path).map_err(|err| Error::failed_to_copy(from, path, err))?;
+				}
+			}
+		}
+
+		Ok(files.into_iter().map(|(path, _)| path).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_instantiate_built_in_templates() {
+		let directory = std::env::temp_dir().join("shiba-test-project-templates");
+		let _ = fs::remove_dir_all(&directory);
+
+		for (name, files) in BUILT_IN_TEMPLATES {
+			let template_directory = directory.join(name);
+			let context = TemplateContext {
+				name,
+				shiba_version: "1.2.3",
+			};
+			let paths = ProjectTemplate::BuiltIn(files)
+				.instantiate(&template_directory, &context, false)
+				.unwrap();
+			assert!(paths.contains(&template_directory.join("shiba.yml")));
+
+			let settings: serde_yaml::Value = serde_yaml::from_str(
+				&fs::read_to_string(template_directory.join("shiba.yml")).unwrap(),
+			)
+			.unwrap();
+			assert_eq!(settings["name"], serde_yaml::Value::from(*name));
+			assert_eq!(settings["shiba-version"], serde_yaml::Value::from("1.2.3"));
+
+			assert!(ProjectTemplate::BuiltIn(files)
+				.instantiate(&template_directory, &context, false)
+				.is_err());
+		}
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn test_generate_code_for_built_in_templates() {
+		use crate::build::{self, BuildOptions, BuildTarget};
+		use crate::compilation::Platform;
+		use crate::compilation_data::Compilation;
+		use crate::executable_target_code_generators;
+		use crate::project_data::Project;
+		use crate::project_files::CodeMap;
+		use crate::target_code_generators::GenerateTargetCodeOptions;
+
+		let directory = std::env::temp_dir().join("shiba-test-project-templates-code");
+		let _ = fs::remove_dir_all(&directory);
+
+		for (name, files) in BUILT_IN_TEMPLATES {
+			let template_directory = directory.join(name);
+			ProjectTemplate::BuiltIn(files)
+				.instantiate(
+					&template_directory,
+					&TemplateContext {
+						name,
+						shiba_version: env!("CARGO_PKG_VERSION"),
+					},
+					false,
+				)
+				.unwrap();
+
+			let mut project = Project::load(
+				&template_directory,
+				BuildTarget::Executable,
+				None,
+				None,
+				&[],
+			)
+			.unwrap();
+			// Avoids depending on GLEW and MSVC being installed.
+			project.development = true;
+			project.settings.executable_target_code_generator =
+				executable_target_code_generators::Settings::X11;
+
+			let build_options = BuildOptions {
+				explain: false,
+				force: true,
+				jobs: None,
+				platform: None,
+				project: &project,
+				target: BuildTarget::Executable,
+			};
+
+			let shader_provider = project
+				.settings
+				.shader_provider
+				.instantiate(&project)
+				.unwrap();
+			let shader_set = shader_provider.provide(&build_options).unwrap();
+			assert!(!shader_set.programs.is_empty());
+
+			let project_codes =
+				build::load_project_files(&project, vec![shader_provider.get_is_path_handled()])
+					.unwrap()
+					.get_compiler_codes(&project.get_template_context(BuildTarget::Executable))
+					.unwrap();
+
+			let target_code_generator =
+				build::instantiate_target_code_generator(&project, BuildTarget::Executable)
+					.unwrap();
+			let mut compilation = Compilation::default();
+			target_code_generator
+				.generate(
+					&build_options,
+					&GenerateTargetCodeOptions {
+						audio_codes: &CodeMap::new(),
+						platform: Platform::X64,
+						project_codes: &project_codes,
+						shader_set: &shader_set,
+					},
+					&mut compilation,
+				)
+				.unwrap();
+
+			// Shader variables and programs are defined as macros, they must not clash with the C++ code.
+			let code = fs::read_to_string(&compilation.jobs[0].path).unwrap();
+			assert!(!code.contains("#define main "));
+			assert!(!code.contains("#define time "));
+		}
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+}
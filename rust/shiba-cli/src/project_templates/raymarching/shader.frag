@@ -0,0 +1,69 @@
+#version 450
+
+uniform float resolutionWidth; // shiba resolution-width
+uniform float resolutionHeight; // shiba resolution-height
+uniform float elapsedTime; // shiba time
+uniform vec3 albedo; // shiba control(default=(1,.6,.3), min=0, max=1, subtype=color)
+uniform float smoothness; // shiba control(default=.3, min=0, max=1)
+
+#pragma shiba attributes
+vec2 position;
+
+#pragma shiba varyings
+vec2 uv;
+
+#pragma shiba outputs
+vec4 color;
+
+#pragma shiba common
+float map(vec3 p)
+{
+	float sphere = length(p - vec3(0., sin(elapsedTime), 0.)) - 1.;
+	float plane = p.y + 1.;
+	float h = clamp(.5 + .5 * (plane - sphere) / smoothness, 0., 1.);
+	return mix(plane, sphere, h) - smoothness * h * (1. - h);
+}
+
+vec3 getNormal(vec3 p)
+{
+	vec2 e = vec2(.001, 0.);
+	return normalize(vec3(
+		map(p + e.xyy) - map(p - e.xyy),
+		map(p + e.yxy) - map(p - e.yxy),
+		map(p + e.yyx) - map(p - e.yyx)));
+}
+
+#pragma shiba vertex image
+void main()
+{
+	uv = position;
+	gl_Position = vec4(position, 0., 1.);
+}
+
+#pragma shiba fragment image
+void main()
+{
+	vec3 origin = vec3(0., 0., 5.);
+	vec3 direction = normalize(vec3(uv.x * resolutionWidth / resolutionHeight, uv.y, -2.));
+
+	float distance = 0.;
+	for (int i = 0; i < {{ variables["max-steps"] }}; ++i)
+	{
+		float d = map(origin + direction * distance);
+		distance += d;
+		if (d < .001 || distance > 100.)
+			break;
+	}
+
+	vec3 sky = vec3(.6, .7, .9);
+	vec3 c = sky;
+	if (distance <= 100.)
+	{
+		vec3 normal = getNormal(origin + direction * distance);
+		vec3 light = normalize(vec3(1., 2., 3.));
+		c = albedo * (.2 + .8 * max(dot(normal, light), 0.));
+		c = mix(c, sky, 1. - exp(-.002 * distance * distance));
+	}
+
+	color = vec4(pow(c, vec3(1. / 2.2)), 1.);
+}
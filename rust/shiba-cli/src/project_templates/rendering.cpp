@@ -0,0 +1,11 @@
+// Executed every frame.
+{% if target == "library" %}
+	glUseProgram(shibaProgramId);
+	shibaCheckGlError();
+{% endif %}
+
+shibaSetUniformsForShader(0);
+shibaCheckGlError();
+
+shibaDrawScreenRect();
+shibaCheckGlError();
@@ -0,0 +1,34 @@
+#version 450
+
+uniform float iResolutionWidth; // shiba resolution-width
+uniform float iResolutionHeight; // shiba resolution-height
+uniform float iTime; // shiba time
+
+#pragma shiba attributes
+vec2 position;
+
+#pragma shiba outputs
+vec4 fragColor;
+
+#pragma shiba common
+#define iResolution vec3(iResolutionWidth, iResolutionHeight, 1.)
+
+// Paste the Shadertoy image code here.
+void mainImage(out vec4 fragColor, in vec2 fragCoord)
+{
+	vec2 uv = fragCoord / iResolution.xy;
+	vec3 col = .5 + .5 * cos(iTime + uv.xyx + vec3(0., 2., 4.));
+	fragColor = vec4(col, 1.);
+}
+
+#pragma shiba vertex image
+void main()
+{
+	gl_Position = vec4(position, 0., 1.);
+}
+
+#pragma shiba fragment image
+void main()
+{
+	mainImage(fragColor, gl_FragCoord.xy);
+}
@@ -1,6 +1,6 @@
 use crate::{Error, Result};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Child, Command};
 use std::time::{Duration, Instant};
 
 pub struct RunOptions<'a> {
@@ -8,11 +8,16 @@ pub struct RunOptions<'a> {
 	pub project_directory: &'a Path,
 }
 
-pub fn run(options: &RunOptions) -> Result<()> {
-	let mut process = Command::new(options.executable_path)
+/// Starts the executable without waiting for it to exit.
+pub fn spawn(options: &RunOptions) -> Result<Child> {
+	Command::new(options.executable_path)
 		.current_dir(options.project_directory)
 		.spawn()
-		.map_err(|err| Error::failed_to_execute(options.executable_path, err))?;
+		.map_err(|err| Error::failed_to_execute(options.executable_path, err))
+}
+
+pub fn run(options: &RunOptions) -> Result<()> {
+	let mut process = spawn(options)?;
 
 	let status = process.wait().unwrap();
 	if !status.success() {
@@ -1,29 +1,40 @@
 use crate::asm_compilers;
 use crate::audio_synthesizers;
+use crate::compilation::Platform;
 use crate::cpp_compilers;
 use crate::executable_linkers;
+use crate::executable_target_code_generators;
 use crate::library_linkers;
+use crate::settings_migration;
+use crate::settings_validation::Issue;
 use crate::shader_minifiers;
 use crate::shader_providers;
 use crate::{Error, Result};
 use ordered_float::OrderedFloat;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Default, Deserialize, Hash, Serialize)]
+pub const FILENAME: &str = "shiba.yml";
+
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Resolution {
 	pub width: Option<u32>,
 	pub height: Option<u32>,
+	#[schemars(with = "Option<f32>")]
 	pub scale: Option<OrderedFloat<f32>>,
 }
 
-#[derive(Debug, Default, Deserialize, Hash, Serialize)]
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct RuntimeSettings {
 	#[serde(default)]
 	pub close_when_finished: bool,
+	#[schemars(with = "Option<f32>")]
 	#[serde(default)]
 	pub duration: Option<OrderedFloat<f32>>,
 	#[serde(default)]
@@ -32,15 +43,62 @@ pub struct RuntimeSettings {
 	pub resolution: Resolution,
 }
 
-#[derive(Debug, Default, Deserialize, Hash)]
+pub type Variables = BTreeMap<String, serde_json::Value>;
+
+/// Parses a variable definition such as "quality=2". The value is parsed as YAML, and falls back to a string.
+pub fn parse_variable(definition: &str) -> Result<(String, serde_json::Value)> {
+	let mut parts = definition.splitn(2, '=');
+	let name = parts.next().unwrap_or_default().trim();
+	let value = parts.next().ok_or_else(|| {
+		Error::message(format!(
+			"Variable definition {} must be of the form name=value.",
+			definition
+		))
+	})?;
+
+	if name.is_empty() {
+		return Err(Error::message(format!(
+			"Variable definition {} has no name.",
+			definition
+		)));
+	}
+
+	let value =
+		serde_yaml::from_str(value).unwrap_or_else(|_| serde_json::Value::from(value.to_string()));
+	Ok((name.to_string(), value))
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Settings {
 	pub shiba_version: Option<String>,
+	/// Path of the project directory or settings file extended, relative to this settings file.
+	// Resolved before deserialization, only declared for the schema.
+	#[allow(dead_code)]
+	#[serde(default, skip_serializing)]
+	pub extends: Option<String>,
 
+	#[serde(default)]
 	pub name: String,
 	pub development: Option<bool>,
+	/// Globs of project files to consider, relative to the project directory.
+	#[serde(default = "default_include")]
+	pub include: Vec<String>,
+	/// Globs of project files and directories to skip, relative to the project directory, in addition to the export directory.
+	#[serde(default)]
+	pub exclude: Vec<String>,
+	/// Globs of C/C++ files (.c, .cc, .cpp, .cxx) compiled as their own translation units, instead of filling code slots.
+	#[serde(default)]
+	pub sources: Vec<String>,
+	/// Platforms for which prebuilt objects and libraries are linked, by glob. Unmatched ones are linked for all platforms.
+	#[serde(default)]
+	pub prebuilt_platforms: BTreeMap<String, BTreeSet<Platform>>,
+	pub platform: Option<Platform>,
 	#[serde(default)]
 	pub runtime: RuntimeSettings,
+	/// Values exposed to shader and code templates.
+	#[serde(default)]
+	pub variables: Variables,
 
 	#[serde(default)]
 	pub asm_compiler: asm_compilers::Settings,
@@ -51,25 +109,424 @@ pub struct Settings {
 	#[serde(default)]
 	pub executable_linker: executable_linkers::Settings,
 	#[serde(default)]
+	pub executable_target_code_generator: executable_target_code_generators::Settings,
+	#[serde(default)]
 	pub library_linker: library_linkers::Settings,
 	#[serde(default)]
 	pub shader_minifier: Option<shader_minifiers::Settings>,
 	#[serde(default)]
 	pub shader_provider: shader_providers::Settings,
+
+	/// Named sets of top-level sections, replacing the base ones when selected.
+	#[schemars(with = "BTreeMap<String, Settings>")]
+	#[serde(default)]
+	pub profiles: BTreeMap<String, Value>,
+	/// Sets of top-level sections by platform, replacing the base ones, after the profile, when building for the platform.
+	#[schemars(with = "BTreeMap<Platform, Settings>")]
+	#[serde(default)]
+	pub platforms: BTreeMap<Platform, Value>,
+
+	/// Directories of the projects extended, nearest first.
+	#[serde(skip)]
+	pub base_directories: Vec<PathBuf>,
+}
+
+fn default_include() -> Vec<String> {
+	vec!["**".to_string()]
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Settings {
+			shiba_version: None,
+			extends: None,
+
+			name: String::default(),
+			development: None,
+			include: default_include(),
+			exclude: vec![],
+			sources: vec![],
+			prebuilt_platforms: BTreeMap::new(),
+			platform: None,
+			runtime: RuntimeSettings::default(),
+			variables: Variables::new(),
+
+			asm_compiler: asm_compilers::Settings::default(),
+			audio_synthesizer: audio_synthesizers::Settings::default(),
+			cpp_compiler: cpp_compilers::Settings::default(),
+			executable_linker: executable_linkers::Settings::default(),
+			executable_target_code_generator: executable_target_code_generators::Settings::default(
+			),
+			library_linker: library_linkers::Settings::default(),
+			shader_minifier: None,
+			shader_provider: shader_providers::Settings::default(),
+
+			profiles: BTreeMap::new(),
+			platforms: BTreeMap::new(),
+
+			base_directories: vec![],
+		}
+	}
 }
 
 impl Settings {
-	pub fn load(project_directory: &Path) -> Result<Self> {
-		let path = project_directory.join("shiba.yml");
+	/// Loads the settings of the project, with the sections of the profile and of the platform applied. Without platform, the one of the settings is used, if any.
+	pub fn load(
+		project_directory: &Path,
+		profile: Option<&str>,
+		platform: Option<Platform>,
+	) -> Result<Self> {
+		let path = project_directory.join(FILENAME);
 
 		if !path.exists() {
+			if let Some(profile) = profile {
+				return Err(Error::message(format!(
+					"Profile {} is not defined.",
+					profile
+				)));
+			}
 			return Ok(Settings::default());
 		}
 
-		let contents =
-			fs::read_to_string(&path).map_err(|err| Error::failed_to_read(&path, err))?;
-		let project: Settings = serde_yaml::from_str(&contents)
-			.map_err(|err| Error::failed_to_deserialize(&contents, err))?;
+		let mut paths = vec![canonicalize(&path)?];
+		let mut value = load_value(&path, &mut paths)?;
+
+		if let Some(profile) = profile {
+			apply_profile(&mut value, profile)?;
+		}
+
+		let platform = platform.or_else(|| {
+			value
+				.get("platform")
+				.and_then(|platform| serde_yaml::from_value(platform.clone()).ok())
+		});
+		if let Some(platform) = platform {
+			apply_platform(&mut value, platform)?;
+		}
+
+		let mut project: Settings = serde_path_to_error::deserialize(value).map_err(|err| {
+			Error::message(format!(
+				"Invalid value at {} in settings: {}. Run shiba validate for details.",
+				err.path(),
+				err.inner()
+			))
+		})?;
+
+		// Settings files extended from the project directory, or twice from the same directory, add no base directory.
+		let mut directories = vec![];
+		for path in &paths {
+			let directory = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+			if !directories.contains(&directory) {
+				directories.push(directory);
+			}
+		}
+		project.base_directories = directories.split_off(1);
 		Ok(project)
 	}
 }
+
+fn canonicalize(path: &Path) -> Result<PathBuf> {
+	fs::canonicalize(path).map_err(|err| Error::failed_to_read(path, err))
+}
+
+/// Returns the path of the settings file extended, given relatively to the extending settings file, either as a project directory or as a settings file.
+pub fn get_extended_path(path: &Path, extends: &str) -> PathBuf {
+	let base_path = path.parent().unwrap_or_else(|| Path::new("")).join(extends);
+	if base_path.is_dir() {
+		base_path.join(FILENAME)
+	} else {
+		base_path
+	}
+}
+
+// Loads the settings file, merged over the ones it extends. Canonical paths of the settings files loaded so far are used to detect cycles.
+fn load_value(path: &Path, paths: &mut Vec<PathBuf>) -> Result<Value> {
+	let contents = fs::read_to_string(path).map_err(|err| Error::failed_to_read(path, err))?;
+	let mut value: Value = serde_yaml::from_str(&contents)
+		.map_err(|err| Error::message(Issue::from_yaml_error(path, &err)))?;
+	settings_migration::check(path, &value)?;
+
+	let extends = match value.as_mapping_mut() {
+		Some(root) => root.remove(&Value::from("extends")),
+		None => None,
+	};
+
+	let extends = match extends {
+		Some(Value::String(extends)) => extends,
+		Some(_) => {
+			return Err(Error::message(format!(
+				"In {}, extends must be a path.",
+				path.to_string_lossy()
+			)))
+		}
+		None => return Ok(value),
+	};
+
+	let base_path = get_extended_path(path, &extends);
+	let canonical_base_path = canonicalize(&base_path)?;
+
+	if paths.contains(&canonical_base_path) {
+		return Err(Error::message(format!(
+			"Settings in {} extend themselves.",
+			canonical_base_path.to_string_lossy()
+		)));
+	}
+	paths.push(canonical_base_path);
+
+	let mut base = load_value(&base_path, paths)?;
+	merge(&mut base, value);
+	Ok(base)
+}
+
+// Mappings are merged recursively, other values are replaced.
+fn merge(base: &mut Value, value: Value) {
+	match (base, value) {
+		(Value::Mapping(base), Value::Mapping(value)) => {
+			for (key, value) in value {
+				match base.get_mut(&key) {
+					Some(base) => merge(base, value),
+					None => {
+						base.insert(key, value);
+					}
+				}
+			}
+		}
+		(base, value) => *base = value,
+	}
+}
+
+// Like the ones of profiles, sections of the platform replace the top-level ones as a whole. Platforms without sections use the top-level ones.
+fn apply_platform(value: &mut Value, platform: Platform) -> Result<()> {
+	let root = value
+		.as_mapping_mut()
+		.ok_or_else(|| Error::message("Settings must be a mapping."))?;
+
+	let sections = match root
+		.get(&Value::from("platforms"))
+		.and_then(|platforms| platforms.get(platform.get_name()))
+	{
+		Some(Value::Mapping(sections)) => sections.clone(),
+		Some(_) => {
+			return Err(Error::message(format!(
+				"Sections of platform {} must be a mapping.",
+				platform.get_name()
+			)))
+		}
+		None => return Ok(()),
+	};
+
+	for (name, section) in sections {
+		root.insert(name, section);
+	}
+
+	Ok(())
+}
+
+// Sections of the profile replace the top-level ones as a whole.
+fn apply_profile(value: &mut Value, profile: &str) -> Result<()> {
+	let root = value
+		.as_mapping_mut()
+		.ok_or_else(|| Error::message("Settings must be a mapping."))?;
+
+	let profiles = root
+		.get(&Value::from("profiles"))
+		.and_then(|profiles| profiles.as_mapping());
+
+	let sections = match profiles.and_then(|profiles| profiles.get(&Value::from(profile))) {
+		Some(Value::Mapping(sections)) => sections.clone(),
+		Some(_) => {
+			return Err(Error::message(format!(
+				"Profile {} must be a mapping.",
+				profile
+			)))
+		}
+		None => {
+			let names = profiles
+				.map(|profiles| {
+					profiles
+						.iter()
+						.filter_map(|(name, _)| name.as_str())
+						.collect::<Vec<_>>()
+						.join(", ")
+				})
+				.unwrap_or_default();
+			return Err(Error::message(format!(
+				"Profile {} is not defined. Available profiles: {}.",
+				profile, names
+			)));
+		}
+	};
+
+	for (name, section) in sections {
+		root.insert(name, section);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_apply_profile() {
+		let mut value: Value = serde_yaml::from_str(
+			"
+name: demo
+development: true
+runtime:
+  close-when-finished: true
+  loading-black-screen: true
+profiles:
+  final:
+    development: false
+    runtime:
+      close-when-finished: true
+",
+		)
+		.unwrap();
+
+		apply_profile(&mut value, "final").unwrap();
+		let settings: Settings = serde_yaml::from_value(value.clone()).unwrap();
+
+		assert_eq!(settings.name, "demo");
+		assert_eq!(settings.development, Some(false));
+		assert!(settings.runtime.close_when_finished);
+		assert!(!settings.runtime.loading_black_screen);
+
+		assert!(apply_profile(&mut value, "party").is_err());
+	}
+
+	#[test]
+	fn test_apply_platform() {
+		let mut value: Value = serde_yaml::from_str(
+			"
+development: true
+platforms:
+  x86:
+    development: false
+    executable-linker:
+      tool: crinkler
+",
+		)
+		.unwrap();
+
+		let mut x64_value = value.clone();
+		apply_platform(&mut x64_value, Platform::X64).unwrap();
+		let settings: Settings = serde_yaml::from_value(x64_value).unwrap();
+		assert_eq!(settings.development, Some(true));
+
+		apply_platform(&mut value, Platform::X86).unwrap();
+		let settings: Settings = serde_yaml::from_value(value).unwrap();
+		assert_eq!(settings.development, Some(false));
+		assert!(matches!(
+			settings.executable_linker,
+			executable_linkers::Settings::Crinkler(_)
+		));
+	}
+
+	#[test]
+	fn test_merge() {
+		let mut base: Value = serde_yaml::from_str(
+			"
+name: base
+exclude: [export, tools]
+runtime:
+  close-when-finished: true
+  resolution:
+    width: 1920
+    height: 1080
+",
+		)
+		.unwrap();
+		let value: Value = serde_yaml::from_str(
+			"
+name: demo
+exclude: [export]
+runtime:
+  resolution:
+    width: 1280
+",
+		)
+		.unwrap();
+
+		merge(&mut base, value);
+		let settings: Settings = serde_yaml::from_value(base).unwrap();
+
+		assert_eq!(settings.name, "demo");
+		assert_eq!(settings.exclude, vec!["export"]);
+		assert!(settings.runtime.close_when_finished);
+		assert_eq!(settings.runtime.resolution.width, Some(1280));
+		assert_eq!(settings.runtime.resolution.height, Some(1080));
+	}
+
+	#[test]
+	fn test_parse_variable() {
+		assert_eq!(
+			parse_variable("quality=2").unwrap(),
+			("quality".to_string(), serde_json::json!(2))
+		);
+		assert_eq!(
+			parse_variable("party=true").unwrap(),
+			("party".to_string(), serde_json::json!(true))
+		);
+		assert_eq!(
+			parse_variable("title=a=b").unwrap(),
+			("title".to_string(), serde_json::json!("a=b"))
+		);
+		assert!(parse_variable("quality").is_err());
+	}
+
+	#[test]
+	fn test_load_malformed() {
+		let directory =
+			std::env::temp_dir().join(format!("shiba-test-settings-{}", std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+		let path = directory.join(FILENAME);
+		fs::write(&path, "name: demo\nruntime:\n  duration: [60\n").unwrap();
+
+		let err = Settings::load(&directory, None, None).unwrap_err();
+		fs::remove_dir_all(&directory).unwrap();
+
+		let message = err.to_string();
+		let location = format!("{}:4:1: ", path.to_string_lossy());
+		assert!(message.starts_with(&location), "{}", message);
+		assert!(!message.contains("name: demo"), "{}", message);
+	}
+
+	#[test]
+	fn test_load_extends() {
+		let directory = std::env::temp_dir().join(format!(
+			"shiba-test-settings-extends-{}",
+			std::process::id()
+		));
+		fs::create_dir_all(&directory).unwrap();
+		fs::write(
+			directory.join(FILENAME),
+			"shiba-version: 0.2.0\nextends: ./base.yml\nname: demo\n",
+		)
+		.unwrap();
+		fs::write(
+			directory.join("base.yml"),
+			"shiba-version: 0.2.0\ndevelopment: false\n",
+		)
+		.unwrap();
+
+		let settings = Settings::load(&directory, None, None);
+
+		fs::write(
+			directory.join("base.yml"),
+			"shiba-version: 0.2.0\nextends: shiba.yml\n",
+		)
+		.unwrap();
+		let err = Settings::load(&directory, None, None).unwrap_err();
+		fs::remove_dir_all(&directory).unwrap();
+
+		let settings = settings.unwrap();
+		assert_eq!(settings.name, "demo");
+		assert_eq!(settings.development, Some(false));
+		assert!(settings.base_directories.is_empty());
+
+		assert!(err.to_string().ends_with("extend themselves."));
+	}
+}
@@ -0,0 +1,280 @@
+use crate::{Error, Result};
+use semver::{Version, VersionReq};
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+const VERSION_KEY: &str = "shiba-version";
+
+/// Change of the settings layout, which older settings files can be rewritten to.
+pub struct Migration {
+	pub description: &'static str,
+	/// First version expecting the new layout.
+	pub since: &'static str,
+	/// Rewrites the settings, returns whether something changed.
+	apply: fn(&mut Mapping) -> bool,
+}
+
+/// Add a migration whenever a release changes the layout, e.g. renames a key, or the meaning of an omitted key.
+pub const MIGRATIONS: &[Migration] = &[
+	Migration {
+		description: "Project files are now discovered recursively, include is set to * to keep only the top-level ones.",
+		since: "0.2.0",
+		apply: keep_top_level_files,
+	},
+	Migration {
+		description: "GLSL files are now shader snippets by default, snippets are set to none to keep them as static files.",
+		since: "0.2.0",
+		apply: keep_glsl_files_static,
+	},
+];
+
+fn keep_top_level_files(root: &mut Mapping) -> bool {
+	let key = Value::from("include");
+	if root.contains_key(&key) {
+		return false;
+	}
+
+	root.insert(key, Value::Sequence(vec![Value::from("*")]));
+	true
+}
+
+fn keep_glsl_files_static(root: &mut Mapping) -> bool {
+	let key = Value::from("shader-provider");
+	if !root.contains_key(&key) {
+		let mut shader_provider = Mapping::new();
+		shader_provider.insert(Value::from("tool"), Value::from("shiba"));
+		root.insert(key.clone(), Value::Mapping(shader_provider));
+	}
+
+	let snippets_key = Value::from("snippets");
+	match root.get_mut(&key).and_then(Value::as_mapping_mut) {
+		Some(shader_provider)
+			if shader_provider.get(&Value::from("tool")) == Some(&Value::from("shiba"))
+				&& !shader_provider.contains_key(&snippets_key) =>
+		{
+			shader_provider.insert(snippets_key, Value::Sequence(vec![]));
+			true
+		}
+		_ => false,
+	}
+}
+
+pub fn get_current_version() -> Version {
+	Version::parse(env!("CARGO_PKG_VERSION")).expect("Failed to parse version.")
+}
+
+// Versions such as "0.1" are requirements compatible with 0.1.x.
+fn parse_version_requirement(path: &Path, shiba_version: &Value) -> Result<VersionReq> {
+	let shiba_version = match shiba_version {
+		Value::String(shiba_version) => shiba_version.clone(),
+		Value::Number(shiba_version) => shiba_version.to_string(),
+		_ => String::new(),
+	};
+	VersionReq::parse(&shiba_version).map_err(|err| {
+		Error::message(format!(
+			"In {}, {} {} is not a valid version: {}.",
+			path.to_string_lossy(),
+			VERSION_KEY,
+			shiba_version,
+			err
+		))
+	})
+}
+
+// Returns the lowest version allowed by the requirement.
+fn get_minimal_version(requirement: &VersionReq) -> Option<Version> {
+	requirement
+		.comparators
+		.iter()
+		.map(|comparator| {
+			Version::new(
+				comparator.major,
+				comparator.minor.unwrap_or(0),
+				comparator.patch.unwrap_or(0),
+			)
+		})
+		.min()
+}
+
+fn get_version_requirement(path: &Path, root: &Mapping) -> Result<Option<VersionReq>> {
+	root.get(&Value::from(VERSION_KEY))
+		.map(|shiba_version| parse_version_requirement(path, shiba_version))
+		.transpose()
+}
+
+fn get_pending_migrations(
+	root: &Mapping,
+	requirement: Option<&VersionReq>,
+) -> Vec<&'static Migration> {
+	let minimal_version = requirement.and_then(get_minimal_version);
+	MIGRATIONS
+		.iter()
+		.filter(|migration| {
+			let since = Version::parse(migration.since).expect("Failed to parse version.");
+			!matches!(&minimal_version, Some(version) if *version >= since)
+		})
+		.filter(|migration| (migration.apply)(&mut root.clone()))
+		.collect()
+}
+
+/// Checks that the settings file is compatible with the running version, and does not use a previous layout.
+pub fn check(path: &Path, value: &Value) -> Result<()> {
+	let root = match value.as_mapping() {
+		Some(root) => root,
+		None => return Ok(()),
+	};
+
+	let requirement = get_version_requirement(path, root)?;
+	let current_version = get_current_version();
+
+	if let Some(requirement) = &requirement {
+		if !requirement.matches(&current_version) {
+			let is_newer = matches!(
+				get_minimal_version(requirement),
+				Some(version) if version > current_version
+			);
+			return Err(Error::message(if is_newer {
+				format!(
+					"{} requires shiba {}, but this is shiba {}. Update shiba.",
+					path.to_string_lossy(),
+					requirement,
+					current_version
+				)
+			} else {
+				format!(
+					"{} was written for shiba {}, but this is shiba {}. Run shiba migrate to update it.",
+					path.to_string_lossy(),
+					requirement,
+					current_version
+				)
+			}));
+		}
+	}
+
+	let migrations = get_pending_migrations(root, requirement.as_ref());
+	if !migrations.is_empty() {
+		return Err(Error::message(format!(
+			"{} uses a previous layout: {} Run shiba migrate to update it.",
+			path.to_string_lossy(),
+			migrations
+				.iter()
+				.map(|migration| migration.description)
+				.collect::<Vec<_>>()
+				.join(" ")
+		)));
+	}
+
+	Ok(())
+}
+
+/// Rewrites the settings into the current layout, and sets the version to the running one if it was outdated. Returns the descriptions of the migrations applied.
+pub fn migrate(path: &Path, value: &mut Value) -> Result<Vec<&'static str>> {
+	let root = value
+		.as_mapping_mut()
+		.ok_or_else(|| Error::message("Settings must be a mapping."))?;
+
+	let requirement = get_version_requirement(path, root)?;
+	let migrations = get_pending_migrations(root, requirement.as_ref());
+	for migration in &migrations {
+		(migration.apply)(root);
+	}
+
+	let current_version = get_current_version();
+	let is_outdated = match &requirement {
+		Some(requirement) => !requirement.matches(&current_version),
+		None => !migrations.is_empty(),
+	};
+	if is_outdated {
+		root.insert(
+			Value::from(VERSION_KEY),
+			Value::from(current_version.to_string()),
+		);
+	}
+
+	Ok(migrations
+		.iter()
+		.map(|migration| migration.description)
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_migrate() {
+		let mut value: Value = serde_yaml::from_str(
+			"
+shiba-version: 0.0.1
+name: demo
+include: ['**']
+shader-provider:
+  tool: shiba
+  snippets: ['**/*.glsl']
+",
+		)
+		.unwrap();
+		let path = Path::new("shiba.yml");
+
+		assert!(check(path, &value).is_err());
+
+		let descriptions = migrate(path, &mut value).unwrap();
+		assert!(descriptions.is_empty());
+		assert!(check(path, &value).is_ok());
+
+		let expected: Value = serde_yaml::from_str(&format!(
+			"
+shiba-version: {}
+name: demo
+include: ['**']
+shader-provider:
+  tool: shiba
+  snippets: ['**/*.glsl']
+",
+			get_current_version()
+		))
+		.unwrap();
+		assert_eq!(value, expected);
+	}
+
+	#[test]
+	fn test_migrate_layout() {
+		let mut value: Value = serde_yaml::from_str(
+			"
+shiba-version: 0.1.0
+name: demo
+",
+		)
+		.unwrap();
+		let path = Path::new("shiba.yml");
+
+		let err = check(path, &value).unwrap_err();
+		assert!(err.to_string().contains("Run shiba migrate"));
+
+		let descriptions = migrate(path, &mut value).unwrap();
+		assert_eq!(
+			descriptions,
+			vec![MIGRATIONS[0].description, MIGRATIONS[1].description]
+		);
+		assert!(check(path, &value).is_ok());
+		assert_eq!(
+			value["include"],
+			serde_yaml::from_str::<Value>("['*']").unwrap()
+		);
+		assert_eq!(
+			value["shader-provider"],
+			serde_yaml::from_str::<Value>("{ tool: shiba, snippets: [] }").unwrap()
+		);
+
+		// Settings of the current layout are left untouched.
+		let descriptions = migrate(path, &mut value).unwrap();
+		assert!(descriptions.is_empty());
+	}
+
+	#[test]
+	fn test_check_newer_version() {
+		let value: Value = serde_yaml::from_str("shiba-version: 99.0.0").unwrap();
+		let err = check(Path::new("shiba.yml"), &value).unwrap_err();
+		assert!(err.to_string().contains("Update shiba."));
+	}
+}
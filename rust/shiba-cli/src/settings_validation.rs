@@ -0,0 +1,407 @@
+use crate::settings::{self, Settings};
+use crate::{Error, Result};
+use schemars::schema::RootSchema;
+use serde_json::{Map, Value as JsonValue};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+/// Problem found in a settings file. Lines and columns start at 1.
+#[derive(Debug)]
+pub struct Issue {
+	pub column: usize,
+	pub line: usize,
+	pub message: String,
+	pub path: PathBuf,
+}
+
+impl Issue {
+	/// Locates a YAML syntax error, or a deserialization error of a YAML file.
+	pub fn from_yaml_error(path: &Path, err: &serde_yaml::Error) -> Self {
+		Issue {
+			column: err.location().map_or(1, |location| location.column()),
+			line: err.location().map_or(1, |location| location.line()),
+			message: err.to_string(),
+			path: path.to_path_buf(),
+		}
+	}
+}
+
+impl fmt::Display for Issue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{}:{}:{}: {}",
+			self.path.to_string_lossy(),
+			self.line,
+			self.column,
+			self.message
+		)
+	}
+}
+
+pub fn get_schema() -> RootSchema {
+	schemars::schema_for!(Settings)
+}
+
+/// Validates the settings file of the project, and the ones it extends.
+pub fn validate(project_directory: &Path) -> Result<Vec<Issue>> {
+	let schema = serde_json::to_value(get_schema()).expect("Failed to dump JSON.");
+
+	let mut issues = vec![];
+	let mut path = project_directory.join(settings::FILENAME);
+	let mut visited_paths = vec![];
+	loop {
+		let canonical_path =
+			fs::canonicalize(&path).map_err(|err| Error::failed_to_read(&path, err))?;
+		if visited_paths.contains(&canonical_path) {
+			issues.push(Issue {
+				column: 1,
+				line: 1,
+				message: "Settings extend themselves.".to_string(),
+				path,
+			});
+			break;
+		}
+		visited_paths.push(canonical_path);
+
+		let contents =
+			fs::read_to_string(&path).map_err(|err| Error::failed_to_read(&path, err))?;
+		match validate_file(&path, &contents, &schema, &mut issues) {
+			Some(extends) => path = settings::get_extended_path(&path, &extends),
+			None => break,
+		}
+	}
+
+	Ok(issues)
+}
+
+// Returns the path extended, if any.
+fn validate_file(
+	path: &Path,
+	contents: &str,
+	schema: &JsonValue,
+	issues: &mut Vec<Issue>,
+) -> Option<String> {
+	let mut location_collector = LocationCollector::default();
+	if let Err(err) = Parser::new(contents.chars()).load(&mut location_collector, false) {
+		issues.push(Issue {
+			column: err.marker().col() + 1,
+			line: err.marker().line(),
+			message: err.to_string(),
+			path: path.to_path_buf(),
+		});
+		return None;
+	}
+	let locations = location_collector.locations;
+
+	let value: Value = match serde_yaml::from_str(contents) {
+		Ok(value) => value,
+		Err(err) => {
+			issues.push(Issue::from_yaml_error(path, &err));
+			return None;
+		}
+	};
+
+	let mut file_issues = vec![];
+	let mut push_issue = |keys: &[String], message: String| {
+		// Falls back to the nearest located parent.
+		let marker = (0..=keys.len())
+			.rev()
+			.find_map(|length| locations.get(&keys[..length]));
+		file_issues.push(Issue {
+			column: marker.map_or(1, |marker| marker.col() + 1),
+			line: marker.map_or(1, |marker| marker.line()),
+			message,
+			path: path.to_path_buf(),
+		});
+	};
+
+	check_types(&value, &[], &mut push_issue);
+
+	// Profile and platform sections are checked as partial settings.
+	for key in &["profiles", "platforms"] {
+		if let Some(sets) = value.get(key).and_then(|sets| sets.as_mapping()) {
+			for (name, sections) in sets {
+				if let Some(name) = name.as_str() {
+					check_types(
+						sections,
+						&[key.to_string(), name.to_string()],
+						&mut push_issue,
+					);
+				}
+			}
+		}
+	}
+
+	if let Ok(json) = serde_json::to_value(&value) {
+		let mut keys = vec![];
+		let mut unknown_keys = vec![];
+		find_unknown_keys(schema, schema, &json, &mut keys, &mut unknown_keys);
+		for keys in unknown_keys {
+			let message = format!("Unknown key {}.", keys.join("."));
+			push_issue(&keys, message);
+		}
+	}
+
+	file_issues.sort_by_key(|issue| (issue.line, issue.column));
+	issues.extend(file_issues);
+
+	value
+		.get("extends")
+		.and_then(|extends| extends.as_str())
+		.map(|extends| extends.to_string())
+}
+
+fn check_types(value: &Value, prefix: &[String], push_issue: &mut impl FnMut(&[String], String)) {
+	if let Err(err) = serde_path_to_error::deserialize::<_, Settings>(value.clone()) {
+		let mut keys = prefix.to_vec();
+		for segment in err.path().iter() {
+			match segment {
+				serde_path_to_error::Segment::Map { key } => keys.push(key.clone()),
+				serde_path_to_error::Segment::Seq { index } => keys.push(index.to_string()),
+				_ => (),
+			}
+		}
+		let message = format!("Invalid value at {}: {}.", keys.join("."), err.inner());
+		push_issue(&keys, message);
+	}
+}
+
+fn resolve_reference<'a>(root: &'a JsonValue, schema: &'a JsonValue) -> &'a JsonValue {
+	match schema
+		.get("$ref")
+		.and_then(|reference| reference.as_str())
+		.and_then(|reference| reference.strip_prefix("#/definitions/"))
+		.and_then(|name| {
+			root.get("definitions")
+				.and_then(|definitions| definitions.get(name))
+		}) {
+		Some(definition) => resolve_reference(root, definition),
+		None => schema,
+	}
+}
+
+// Collects the schemas applying to the object, following references and combinations. Alternatives of tool-tagged enums are selected by the tool.
+fn collect_object_schemas<'a>(
+	root: &'a JsonValue,
+	schema: &'a JsonValue,
+	object: &Map<String, JsonValue>,
+	schemas: &mut Vec<&'a JsonValue>,
+) {
+	let schema = resolve_reference(root, schema);
+	schemas.push(schema);
+
+	if let Some(all_of) = schema.get("allOf").and_then(|all_of| all_of.as_array()) {
+		for schema in all_of {
+			collect_object_schemas(root, schema, object, schemas);
+		}
+	}
+
+	for combination in &["anyOf", "oneOf"] {
+		if let Some(alternatives) = schema
+			.get(combination)
+			.and_then(|alternatives| alternatives.as_array())
+		{
+			for alternative in alternatives {
+				let mut alternative_schemas = vec![];
+				collect_object_schemas(root, alternative, object, &mut alternative_schemas);
+				if alternative_schemas
+					.iter()
+					.all(|schema| is_object_matching(schema, object))
+				{
+					schemas.extend(alternative_schemas);
+				}
+			}
+		}
+	}
+}
+
+fn is_object_matching(schema: &JsonValue, object: &Map<String, JsonValue>) -> bool {
+	if schema.get("type") == Some(&JsonValue::from("null")) {
+		return false;
+	}
+
+	let tool = object.get("tool");
+	match schema.pointer("/properties/tool/enum") {
+		Some(JsonValue::Array(tools)) => matches!(tool, Some(tool) if tools.contains(tool)),
+		_ => true,
+	}
+}
+
+fn find_unknown_keys(
+	root: &JsonValue,
+	schema: &JsonValue,
+	value: &JsonValue,
+	keys: &mut Vec<String>,
+	unknown_keys: &mut Vec<Vec<String>>,
+) {
+	let object = match value {
+		JsonValue::Object(object) => object,
+		_ => return,
+	};
+
+	let mut schemas = vec![];
+	collect_object_schemas(root, schema, object, &mut schemas);
+
+	let additional_schema = schemas
+		.iter()
+		.find_map(|schema| schema.get("additionalProperties"));
+	let is_closed = schemas
+		.iter()
+		.any(|schema| schema.get("properties").is_some())
+		&& matches!(additional_schema, None | Some(JsonValue::Bool(false)));
+
+	for (key, item) in object {
+		let item_schema = schemas
+			.iter()
+			.find_map(|schema| {
+				schema
+					.get("properties")
+					.and_then(|properties| properties.get(key))
+			})
+			.or_else(|| additional_schema.filter(|schema| schema.is_object()));
+
+		keys.push(key.clone());
+		match item_schema {
+			Some(item_schema) => find_unknown_keys(root, item_schema, item, keys, unknown_keys),
+			None if is_closed => unknown_keys.push(keys.clone()),
+			None => (),
+		}
+		keys.pop();
+	}
+}
+
+enum Frame {
+	Mapping {
+		is_expecting_key: bool,
+		key: Option<String>,
+	},
+	Sequence {
+		index: usize,
+	},
+}
+
+// Locates mapping keys and sequence items by their path.
+#[derive(Default)]
+struct LocationCollector {
+	frames: Vec<Frame>,
+	locations: BTreeMap<Vec<String>, Marker>,
+}
+
+impl LocationCollector {
+	fn get_keys(&self) -> Vec<String> {
+		self.frames
+			.iter()
+			.filter_map(|frame| match frame {
+				Frame::Mapping { key, .. } => key.clone(),
+				Frame::Sequence { index } => Some(index.to_string()),
+			})
+			.collect()
+	}
+
+	fn start_node(&mut self, scalar: Option<&str>, mark: Marker) {
+		match self.frames.last_mut() {
+			Some(Frame::Mapping {
+				is_expecting_key: true,
+				key,
+			}) => {
+				*key = scalar.map(|scalar| scalar.to_string());
+				if key.is_none() {
+					return;
+				}
+			}
+			Some(Frame::Sequence { .. }) => (),
+			_ => return,
+		}
+
+		let keys = self.get_keys();
+		self.locations.entry(keys).or_insert(mark);
+	}
+
+	fn end_node(&mut self) {
+		match self.frames.last_mut() {
+			Some(Frame::Mapping {
+				is_expecting_key, ..
+			}) => *is_expecting_key = !*is_expecting_key,
+			Some(Frame::Sequence { index }) => *index += 1,
+			None => (),
+		}
+	}
+}
+
+impl MarkedEventReceiver for LocationCollector {
+	fn on_event(&mut self, event: Event, mark: Marker) {
+		match event {
+			Event::Alias(_) => {
+				self.start_node(None, mark);
+				self.end_node();
+			}
+			Event::Scalar(value, ..) => {
+				self.start_node(Some(&value), mark);
+				self.end_node();
+			}
+			Event::MappingStart(_) => {
+				self.start_node(None, mark);
+				self.frames.push(Frame::Mapping {
+					is_expecting_key: true,
+					key: None,
+				});
+			}
+			Event::SequenceStart(_) => {
+				self.start_node(None, mark);
+				self.frames.push(Frame::Sequence { index: 0 });
+			}
+			Event::MappingEnd | Event::SequenceEnd => {
+				self.frames.pop();
+				self.end_node();
+			}
+			_ => (),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_validate_file() {
+		let schema = serde_json::to_value(get_schema()).unwrap();
+		let contents = "
+name: demo
+developement: true
+cpp-compiler:
+  tool: gcc
+  argz: [-O2]
+runtime:
+  duration: long
+profiles:
+  final:
+    shader-minifier:
+      tool: shader-minifier
+      extra: 1
+";
+
+		let mut issues = vec![];
+		validate_file(Path::new("shiba.yml"), contents, &schema, &mut issues);
+
+		let issues = issues
+			.iter()
+			.map(|issue| (issue.line, issue.column, issue.message.as_str()))
+			.collect::<Vec<_>>();
+		assert_eq!(issues.len(), 4, "{:?}", issues);
+		assert_eq!(issues[0], (3, 1, "Unknown key developement."));
+		assert_eq!(issues[1], (6, 3, "Unknown key cpp-compiler.argz."));
+		assert_eq!(issues[2].0, 8);
+		assert!(issues[2].2.starts_with("Invalid value at runtime.duration"));
+		assert_eq!(
+			issues[3],
+			(13, 7, "Unknown key profiles.final.shader-minifier.extra.")
+		);
+	}
+}
@@ -1,15 +1,24 @@
 use super::{shader_minifier, ShaderMinifier};
 use crate::project_data::Project;
 use crate::Result;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "ShaderMinifierSettings")]
 #[serde(rename_all = "kebab-case", tag = "tool")]
 pub enum Settings {
 	ShaderMinifier,
 }
 
 impl Settings {
+	/// Returns the names of the tools to configure.
+	pub fn get_tool_names(&self) -> Vec<&'static str> {
+		match self {
+			Settings::ShaderMinifier => vec!["shader-minifier"],
+		}
+	}
+
 	pub fn instantiate<'a>(
 		&'a self,
 		project: &'a Project,
@@ -4,9 +4,9 @@ mod types;
 use self::types::*;
 use super::ShaderMinifier;
 use crate::build::BuildOptions;
-use crate::hash_extra;
+use crate::build_cache;
+use crate::hash_extra::{self, ToolIdentity};
 use crate::parsers::glsl;
-use crate::paths::BUILD_ROOT_DIRECTORY;
 use crate::project_data::Project;
 use crate::shader_data::{
 	ShaderProgram, ShaderProgramMap, ShaderSections, ShaderSet, ShaderVariable, ShaderVariableKind,
@@ -17,19 +17,21 @@ use serde::Serialize;
 use serde_json;
 use std::cell::Cell;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::str;
 use tera::{Context, Tera};
 
 pub struct ShaderMinifierShaderMinifier {
 	exe_path: PathBuf,
+	exe_tool: ToolIdentity,
 	tera: Tera,
 }
 
 impl ShaderMinifierShaderMinifier {
 	pub fn new(project: &Project) -> Result<Self> {
 		let exe_path = project.configuration.get_path("shader-minifier");
+		let exe_tool = hash_extra::get_tool_identity(&exe_path);
 
 		let mut tera = Tera::default();
 
@@ -39,7 +41,11 @@ impl ShaderMinifierShaderMinifier {
 		)
 		.expect("Failed to add template.");
 
-		Ok(ShaderMinifierShaderMinifier { exe_path, tera })
+		Ok(ShaderMinifierShaderMinifier {
+			exe_path,
+			exe_tool,
+			tera,
+		})
 	}
 }
 
@@ -51,20 +57,22 @@ impl ShaderMinifier for ShaderMinifierShaderMinifier {
 	) -> Result<ShaderSet> {
 		const OUTPUT_FILENAME: &str = "shader-descriptor.json";
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
-			exe_path: &'a Path,
+			exe_tool: &'a ToolIdentity,
 			original_shader_set: &'a ShaderSet,
 		}
 
 		let inputs = Inputs {
-			exe_path: &self.exe_path,
+			exe_tool: &self.exe_tool,
 			original_shader_set,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "shader-minifiers/shader-minifier", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			let json = fs::read_to_string(&build_cache_path)
 				.map_err(|err| Error::failed_to_read(&build_cache_path, err))?;
 			let shader_set = serde_json::from_str(&json)
@@ -114,11 +122,7 @@ impl ShaderMinifier for ShaderMinifierShaderMinifier {
 				Error::failed_to_render_template("shader-minifier-shader-minifier", err)
 			})?;
 
-		let build_directory = BUILD_ROOT_DIRECTORY
-			.join("shader-minifiers")
-			.join("shader-minifier");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let input_path = build_directory.join("shader.glsl");
 		let output_path = build_directory.join("shader.min.glsl");
@@ -232,8 +236,7 @@ impl ShaderMinifier for ShaderMinifierShaderMinifier {
 		};
 
 		let json = serde_json::to_string(&shader_set).expect("Failed to dump JSON.");
-		fs::write(&build_cache_path, json)
-			.map_err(|err| Error::failed_to_write(&build_cache_path, err))?;
+		build_cache::write_atomically(&build_cache_path, json)?;
 
 		Ok(shader_set)
 	}
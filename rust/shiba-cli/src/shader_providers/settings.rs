@@ -1,9 +1,11 @@
 use super::{shiba, ShaderProvider};
 use crate::project_data::Project;
 use crate::Result;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Hash)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(rename = "ShaderProviderSettings")]
 #[serde(rename_all = "kebab-case", tag = "tool")]
 pub enum Settings {
 	Shiba(shiba::ShibaSettings),
@@ -5,11 +5,11 @@ mod types;
 pub use self::settings::ShibaSettings;
 use self::types::*;
 use super::ShaderProvider;
-use crate::build::{BuildOptions, BuildTarget};
-use crate::hash_extra;
+use crate::build::BuildOptions;
+use crate::build_cache;
 use crate::parsers::glsl;
-use crate::project_data::Project;
-use crate::project_files::{FileConsumer, IsPathHandled};
+use crate::project_data::{Project, TemplateContext};
+use crate::project_files::{self, FileConsumer, IsPathHandled};
 use crate::shader_data::{
 	ShaderConstVariable, ShaderProgram, ShaderSet, ShaderUniformArray, ShaderVariableKind,
 };
@@ -18,46 +18,77 @@ use regex::Regex;
 use serde::Serialize;
 use serde_json;
 use std::cell::Cell;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use tera::{Context, Tera};
 
-#[derive(Serialize)]
-struct OwnContext {
-	development: bool,
-	target: BuildTarget,
-}
-
 pub struct ShibaShaderProvider<'a> {
 	project: &'a Project,
 
 	contents: String,
 	path: PathBuf,
+	snippet_paths: Vec<PathBuf>,
+	snippets: BTreeMap<String, String>,
 }
 
 impl<'a> ShibaShaderProvider<'a> {
 	pub fn new(project: &'a Project, settings: &'a ShibaSettings) -> Result<Self> {
-		let path = project.directory.join(&settings.filename);
+		let path = project.find_file(&settings.filename);
 		let contents =
 			fs::read_to_string(&path).map_err(|err| Error::failed_to_read(&path, err))?;
 
+		// Snippets are named by their relative path, the project ones override the base ones.
+		let include = project_files::build_glob_set(&settings.snippets)?;
+		let exclude = project_files::build_exclude_glob_set(&project.settings.exclude)?;
+		let mut snippet_paths = vec![];
+		let mut snippets = BTreeMap::new();
+		for directory in project.get_directories() {
+			for snippet_path in project_files::walk_files(directory, &include, &exclude)? {
+				if snippet_path == path {
+					continue;
+				}
+
+				let name = snippet_path
+					.strip_prefix(directory)
+					.unwrap_or(&snippet_path)
+					.components()
+					.map(|component| component.as_os_str().to_string_lossy())
+					.collect::<Vec<_>>()
+					.join("/");
+				if snippets.contains_key(&name) {
+					continue;
+				}
+
+				let snippet = fs::read_to_string(&snippet_path)
+					.map_err(|err| Error::failed_to_read(&snippet_path, err))?;
+				snippets.insert(name, snippet);
+				snippet_paths.push(snippet_path);
+			}
+		}
+
 		Ok(ShibaShaderProvider {
 			project,
 			contents,
 			path,
+			snippet_paths,
+			snippets,
 		})
 	}
 
 	fn render(&self, build_options: &BuildOptions, code: &str) -> Result<String> {
 		let mut tera = Tera::default();
 
+		tera.add_raw_templates(
+			self.snippets
+				.iter()
+				.map(|(name, snippet)| (name.as_str(), snippet.as_str())),
+		)
+		.map_err(|err| Error::failed_to_render_template("shader-provider-shiba", err))?;
 		tera.add_raw_template("shader-provider-shiba", code)
-			.expect("Failed to add template.");
+			.map_err(|err| Error::failed_to_render_template("shader-provider-shiba", err))?;
 
-		let context = OwnContext {
-			development: self.project.development,
-			target: build_options.target,
-		};
+		let context = self.project.get_template_context(build_options.target);
 
 		let code = tera
 			.render(
@@ -149,22 +180,24 @@ impl<'a> ShaderProvider for ShibaShaderProvider<'a> {
 	fn provide(&self, build_options: &BuildOptions) -> Result<ShaderSet> {
 		const OUTPUT_FILENAME: &str = "shader-descriptor.json";
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
-			development: bool,
 			contents: &'a String,
-			target: BuildTarget,
+			context: TemplateContext<'a>,
+			snippets: &'a BTreeMap<String, String>,
 		}
 
 		let inputs = Inputs {
-			development: self.project.development,
 			contents: &self.contents,
-			target: build_options.target,
+			context: self.project.get_template_context(build_options.target),
+			snippets: &self.snippets,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "shader-providers/shiba", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			let json = fs::read_to_string(&build_cache_path)
 				.map_err(|err| Error::failed_to_read(&build_cache_path, err))?;
 			let shader_set = serde_json::from_str(&json)
@@ -287,8 +320,7 @@ impl<'a> ShaderProvider for ShibaShaderProvider<'a> {
 		}
 
 		let json = serde_json::to_string(&shader_set).expect("Failed to dump JSON.");
-		fs::write(&build_cache_path, json)
-			.map_err(|err| Error::failed_to_write(&build_cache_path, err))?;
+		build_cache::write_atomically(&build_cache_path, json)?;
 
 		Ok(shader_set)
 	}
@@ -296,7 +328,13 @@ impl<'a> ShaderProvider for ShibaShaderProvider<'a> {
 
 impl FileConsumer for ShibaShaderProvider<'_> {
 	fn get_is_path_handled<'b, 'a: 'b>(&'a self) -> IsPathHandled<'b> {
-		Box::new(move |path| path == self.path)
+		Box::new(move |path| {
+			path == self.path
+				|| self
+					.snippet_paths
+					.iter()
+					.any(|snippet_path| path == snippet_path)
+		})
 	}
 }
 
@@ -1,20 +1,29 @@
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 fn default_filename() -> String {
 	"shader.frag".to_string()
 }
 
-#[derive(Debug, Deserialize, Hash)]
+fn default_snippets() -> Vec<String> {
+	vec!["**/*.glsl".to_string()]
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ShibaSettings {
 	#[serde(default = "default_filename")]
 	pub filename: String,
+	/// Globs of files which can be included from the shader, e.g. {% include "common/noise.glsl" %}. All GLSL files by default.
+	#[serde(default = "default_snippets")]
+	pub snippets: Vec<String>,
 }
 
 impl Default for ShibaSettings {
 	fn default() -> Self {
 		ShibaSettings {
 			filename: default_filename(),
+			snippets: default_snippets(),
 		}
 	}
 }
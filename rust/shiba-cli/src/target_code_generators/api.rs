@@ -1,4 +1,5 @@
 use crate::build::BuildTarget;
+use crate::compilation::System;
 use crate::configuration::Configuration;
 use crate::project_files::CodeMap;
 use crate::shader_codes::ShaderCodes;
@@ -24,6 +25,9 @@ template_enum! {
 	ShaderLoading: "shader_loading",
 }
 
+/// Header of the system GL development package, declaring the extensions on Linux.
+const SYSTEM_GLEXT_PATH: &str = "/usr/include/GL/glext.h";
+
 #[derive(Serialize)]
 pub struct API {
 	pub api: String,
@@ -35,7 +39,7 @@ pub struct API {
 	pub shader_loading: String,
 }
 
-#[derive(Hash)]
+#[derive(Serialize)]
 pub struct APIGeneratorInputs<'a> {
 	pub glew_path: &'a PathBuf,
 }
@@ -88,6 +92,7 @@ impl APIGenerator {
 		project_codes: &CodeMap,
 		shader_set: &ShaderSet,
 		development: bool,
+		system: System,
 		target: BuildTarget,
 	) -> Result<API> {
 		let shader_codes = ShaderCodes::load(shader_set);
@@ -157,6 +162,7 @@ impl APIGenerator {
 			development: bool,
 			shader_programs: &'a ShaderProgramMap,
 			shader_program_count: usize,
+			system: System,
 			target: BuildTarget,
 		}
 
@@ -164,6 +170,7 @@ impl APIGenerator {
 			development,
 			shader_programs: &shader_set.programs,
 			shader_program_count: shader_set.programs.len(),
+			system,
 			target,
 		};
 		let api = self.render_template(Template::API, &api_context)?;
@@ -265,16 +272,14 @@ impl APIGenerator {
 			let mut constants = vec![];
 			let mut functions = vec![];
 
-			let glew_path = self
-				.glew_path
-				.join("include")
-				.join("GL")
-				.join("glew.h")
-				.to_string_lossy()
-				.to_string();
+			// Linux builds without GLEW declare the extensions like the system headers do.
+			let header_path = match system {
+				System::Linux => PathBuf::from(SYSTEM_GLEXT_PATH),
+				System::Windows => self.glew_path.join("include").join("GL").join("glew.h"),
+			};
 
-			let glew_contents = fs::read_to_string(&glew_path)
-				.map_err(|err| Error::failed_to_read(&glew_path, err))?;
+			let header_contents = fs::read_to_string(&header_path)
+				.map_err(|err| Error::failed_to_read(&header_path, err))?;
 
 			{
 				let mut parse = |code| {
@@ -286,7 +291,7 @@ impl APIGenerator {
 						{
 							let declaration_re =
 								Regex::new(&format!(r"#define {} .+", name)).expect("Bad regex.");
-							if let Some(mat) = declaration_re.find(&glew_contents) {
+							if let Some(mat) = declaration_re.find(&header_contents) {
 								constants.push(OpenGLExtConstant {
 									name: name.to_string(),
 									declaration: mat.as_str().to_string(),
@@ -302,11 +307,11 @@ impl APIGenerator {
 						{
 							let typedef_name = format!("PFN{}PROC", name.to_uppercase());
 							let typedef_declaration_re = Regex::new(&format!(
-								r"typedef \w+ \(GLAPIENTRY \* {}\).+",
+								r"typedef \w+ \((?:GLAPIENTRY \*|APIENTRYP) {}\).+",
 								typedef_name
 							))
 							.expect("Bad regex.");
-							if let Some(mat) = typedef_declaration_re.find(&glew_contents) {
+							if let Some(mat) = typedef_declaration_re.find(&header_contents) {
 								functions.push(OpenGLExtFunction {
 									name: name.to_string(),
 									typedef_declaration: mat.as_str().to_string(),
@@ -342,12 +347,14 @@ impl APIGenerator {
 			development: bool,
 			opengl_ext_constants: &'a Option<Vec<OpenGLExtConstant>>,
 			opengl_ext_functions: &'a Option<Vec<OpenGLExtFunction>>,
+			system: System,
 		}
 
 		let opengl_declarations_context = OpenGLDeclarationContext {
 			development,
 			opengl_ext_constants: &opengl_ext_constants,
 			opengl_ext_functions: &opengl_ext_functions,
+			system,
 		};
 		let opengl_declarations =
 			self.render_template(Template::OpenGLDeclarations, &opengl_declarations_context)?;
@@ -357,12 +364,16 @@ impl APIGenerator {
 			development: bool,
 			opengl_ext_constants: &'a Option<Vec<OpenGLExtConstant>>,
 			opengl_ext_functions: &'a Option<Vec<OpenGLExtFunction>>,
+			system: System,
+			target: BuildTarget,
 		}
 
 		let opengl_loading_context = OpenGLLoadingContext {
 			development,
 			opengl_ext_constants: &opengl_ext_constants,
 			opengl_ext_functions: &opengl_ext_functions,
+			system,
+			target,
 		};
 		let opengl_loading =
 			self.render_template(Template::OpenGLLoading, &opengl_loading_context)?;
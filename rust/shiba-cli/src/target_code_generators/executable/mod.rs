@@ -1,11 +1,9 @@
 use super::api::{APIGenerator, APIGeneratorInputs, API};
 use super::{GenerateTargetCodeOptions, TargetCodeGenerator};
 use crate::build::{BuildOptions, BuildTarget};
-use crate::compilation::{CompilationJobEmitter, Platform};
+use crate::build_cache;
+use crate::compilation::{CompilationJobEmitter, Platform, System};
 use crate::compilation_data::{Compilation, CompilationJob, CompilationJobKind};
-use crate::hash_extra;
-use crate::msvc;
-use crate::paths::BUILD_ROOT_DIRECTORY;
 use crate::project_data::Project;
 use crate::project_files::CodeMap;
 use crate::settings::RuntimeSettings;
@@ -20,7 +18,6 @@ use tera::{Context, Tera};
 pub struct ExecutableTargetCodeGenerator {
 	api_generator: APIGenerator,
 	glew_path: PathBuf,
-	msvc_command_generator: msvc::CommandGenerator,
 	tera: Tera,
 }
 
@@ -28,7 +25,6 @@ impl ExecutableTargetCodeGenerator {
 	pub fn new(project: &Project) -> Result<Self> {
 		let api_generator = APIGenerator::new(&project.configuration)?;
 		let glew_path = project.configuration.get_path("glew");
-		let msvc_command_generator = msvc::CommandGenerator::new()?;
 
 		let mut tera = Tera::default();
 
@@ -38,13 +34,25 @@ impl ExecutableTargetCodeGenerator {
 		Ok(ExecutableTargetCodeGenerator {
 			api_generator,
 			glew_path,
-			msvc_command_generator,
 			tera,
 		})
 	}
 }
 
 impl TargetCodeGenerator for ExecutableTargetCodeGenerator {
+	fn get_code_slots(&self) -> &'static [&'static str] {
+		&["declarations", "initialization", "rendering"]
+	}
+
+	// Without development, extensions are declared from the GLEW header.
+	fn get_tool_names(&self) -> Vec<&'static str> {
+		vec!["glew"]
+	}
+
+	fn get_system(&self) -> System {
+		System::Windows
+	}
+
 	fn generate(
 		&self,
 		build_options: &BuildOptions,
@@ -53,12 +61,11 @@ impl TargetCodeGenerator for ExecutableTargetCodeGenerator {
 	) -> Result<()> {
 		const OUTPUT_FILENAME: &str = "executable.cpp";
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
 			api_generator: APIGeneratorInputs<'a>,
 			development: bool,
 			glew_path: &'a Path,
-			msvc_command_generator: msvc::CommandGeneratorInputs<'a>,
 			options: &'a GenerateTargetCodeOptions<'a>,
 			runtime_settings: &'a RuntimeSettings,
 			target: BuildTarget,
@@ -68,12 +75,13 @@ impl TargetCodeGenerator for ExecutableTargetCodeGenerator {
 			api_generator: self.api_generator.get_inputs(),
 			development: build_options.project.development,
 			glew_path: &self.glew_path,
-			msvc_command_generator: self.msvc_command_generator.get_inputs(),
 			options,
 			runtime_settings: &build_options.project.settings.runtime,
 			target: build_options.target,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "target-code-generators/executable", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
 
 		compilation
@@ -105,7 +113,7 @@ impl TargetCodeGenerator for ExecutableTargetCodeGenerator {
 			include_paths,
 		});
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			return Ok(());
 		}
 
@@ -113,6 +121,7 @@ impl TargetCodeGenerator for ExecutableTargetCodeGenerator {
 			options.project_codes,
 			options.shader_set,
 			build_options.project.development,
+			System::Windows,
 			BuildTarget::Executable,
 		)?;
 
@@ -146,19 +155,14 @@ impl TargetCodeGenerator for ExecutableTargetCodeGenerator {
 			)
 			.map_err(|err| Error::failed_to_render_template("executable", err))?;
 
-		let build_directory = BUILD_ROOT_DIRECTORY
-			.join("target-code-generators")
-			.join("executable");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let source_path = build_directory.join("executable.cpp");
 		fs::write(&source_path, contents.as_bytes())
 			.map_err(|err| Error::failed_to_write(&source_path, err))?;
 
 		let copy_from = build_directory.join("executable.cpp");
-		fs::copy(&copy_from, &build_cache_path)
-			.map_err(|err| Error::failed_to_copy(&copy_from, &build_cache_path, err))?;
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
 
 		Ok(())
 	}
@@ -1,11 +1,9 @@
 use super::api::{APIGenerator, APIGeneratorInputs, API};
 use super::{GenerateTargetCodeOptions, TargetCodeGenerator};
 use crate::build::{BuildOptions, BuildTarget};
-use crate::compilation::{CompilationJobEmitter, Platform};
+use crate::build_cache;
+use crate::compilation::{CompilationJobEmitter, Platform, System};
 use crate::compilation_data::{Compilation, CompilationJob, CompilationJobKind};
-use crate::hash_extra;
-use crate::msvc;
-use crate::paths::BUILD_ROOT_DIRECTORY;
 use crate::project_data::Project;
 use crate::project_files::CodeMap;
 use crate::settings::RuntimeSettings;
@@ -19,8 +17,9 @@ use tera::{Context, Tera};
 
 pub struct LibraryTargetCodeGenerator {
 	api_generator: APIGenerator,
+	development: bool,
 	glew_path: PathBuf,
-	msvc_command_generator: msvc::CommandGenerator,
+	system: System,
 	tera: Tera,
 }
 
@@ -28,7 +27,8 @@ impl LibraryTargetCodeGenerator {
 	pub fn new(project: &Project) -> Result<Self> {
 		let api_generator = APIGenerator::new(&project.configuration)?;
 		let glew_path = project.configuration.get_path("glew");
-		let msvc_command_generator = msvc::CommandGenerator::new()?;
+		// The library is built for the system of its linker.
+		let system = project.settings.library_linker.get_system();
 
 		let mut tera = Tera::default();
 
@@ -37,14 +37,32 @@ impl LibraryTargetCodeGenerator {
 
 		Ok(LibraryTargetCodeGenerator {
 			api_generator,
+			development: project.development,
 			glew_path,
-			msvc_command_generator,
+			system,
 			tera,
 		})
 	}
 }
 
 impl TargetCodeGenerator for LibraryTargetCodeGenerator {
+	fn get_code_slots(&self) -> &'static [&'static str] {
+		&["declarations", "initialization", "rendering"]
+	}
+
+	fn get_system(&self) -> System {
+		self.system
+	}
+
+	// Without development, Linux libraries declare extensions from the system headers.
+	fn get_tool_names(&self) -> Vec<&'static str> {
+		if self.development || self.system == System::Windows {
+			vec!["glew"]
+		} else {
+			vec![]
+		}
+	}
+
 	fn generate(
 		&self,
 		build_options: &BuildOptions,
@@ -53,14 +71,14 @@ impl TargetCodeGenerator for LibraryTargetCodeGenerator {
 	) -> Result<()> {
 		const OUTPUT_FILENAME: &str = "library.cpp";
 
-		#[derive(Hash)]
+		#[derive(Serialize)]
 		struct Inputs<'a> {
 			api_generator: APIGeneratorInputs<'a>,
 			development: bool,
 			glew_path: &'a Path,
-			msvc_command_generator: msvc::CommandGeneratorInputs<'a>,
 			options: &'a GenerateTargetCodeOptions<'a>,
 			runtime_settings: &'a RuntimeSettings,
+			system: System,
 			target: BuildTarget,
 		}
 
@@ -68,34 +86,59 @@ impl TargetCodeGenerator for LibraryTargetCodeGenerator {
 			api_generator: self.api_generator.get_inputs(),
 			development: build_options.project.development,
 			glew_path: &self.glew_path,
-			msvc_command_generator: self.msvc_command_generator.get_inputs(),
 			options,
 			runtime_settings: &build_options.project.settings.runtime,
+			system: self.system,
 			target: build_options.target,
 		};
-		let build_cache_directory = hash_extra::get_build_cache_directory(&inputs)?;
+		let build_cache_entry =
+			build_cache::lock_entry(build_options, "target-code-generators/library", &inputs)?;
+		let build_cache_directory = build_cache_entry.get_directory();
 		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
 
-		compilation
-			.common
-			.link_dependencies
-			.insert(PathBuf::from("opengl32.lib"));
-
-		if build_options.project.development {
-			compilation.common.link_dependencies.insert(
-				self.glew_path
-					.join("lib")
-					.join("Release")
-					.join(match options.platform {
-						Platform::X64 => "x64",
-						Platform::X86 => "Win32",
-					})
-					.join("glew32s.lib"),
-			);
+		match self.system {
+			System::Linux => {
+				compilation
+					.common
+					.link_dependencies
+					.insert(PathBuf::from("X11"));
+				compilation
+					.common
+					.link_dependencies
+					.insert(PathBuf::from("GL"));
+
+				if build_options.project.development {
+					compilation
+						.common
+						.link_dependencies
+						.insert(PathBuf::from("GLEW"));
+				}
+			}
+			System::Windows => {
+				compilation
+					.common
+					.link_dependencies
+					.insert(PathBuf::from("opengl32.lib"));
+
+				if build_options.project.development {
+					compilation.common.link_dependencies.insert(
+						self.glew_path
+							.join("lib")
+							.join("Release")
+							.join(match options.platform {
+								Platform::X64 => "x64",
+								Platform::X86 => "Win32",
+							})
+							.join("glew32s.lib"),
+					);
+				}
+			}
 		}
 
 		let mut include_paths = BTreeSet::new();
-		include_paths.insert(self.glew_path.join("include"));
+		if build_options.project.development || self.system == System::Windows {
+			include_paths.insert(self.glew_path.join("include"));
+		}
 
 		compilation.jobs.push(CompilationJob {
 			kind: CompilationJobKind::Cpp,
@@ -103,7 +146,7 @@ impl TargetCodeGenerator for LibraryTargetCodeGenerator {
 			include_paths,
 		});
 
-		if !build_options.force && build_cache_path.exists() {
+		if build_cache_entry.is_hit(&build_cache_path) {
 			return Ok(());
 		}
 
@@ -111,24 +154,29 @@ impl TargetCodeGenerator for LibraryTargetCodeGenerator {
 			options.project_codes,
 			options.shader_set,
 			build_options.project.development,
+			self.system,
 			BuildTarget::Library,
 		)?;
 
 		#[derive(Serialize)]
 		struct OwnContext<'a> {
 			api: &'a API,
+			development: bool,
 			project_codes: &'a CodeMap,
 			runtime_settings: &'a RuntimeSettings,
 			shader_set: &'a ShaderSet,
 			shader_program_count: usize,
+			system: System,
 		}
 
 		let context = OwnContext {
 			api: &api,
+			development: build_options.project.development,
 			project_codes: &options.project_codes,
 			runtime_settings: &build_options.project.settings.runtime,
 			shader_set: &options.shader_set,
 			shader_program_count: options.shader_set.programs.len(),
+			system: self.system,
 		};
 		let contents = self
 			.tera
@@ -138,18 +186,13 @@ impl TargetCodeGenerator for LibraryTargetCodeGenerator {
 			)
 			.map_err(|err| Error::failed_to_render_template("library", err))?;
 
-		let build_directory = BUILD_ROOT_DIRECTORY
-			.join("target-code-generators")
-			.join("library");
-		fs::create_dir_all(&build_directory)
-			.map_err(|err| Error::failed_to_create_directory(&build_directory, err))?;
+		let build_directory = build_cache_entry.create_scratch_directory()?;
 
 		let source_path = build_directory.join("library.cpp");
 		fs::write(&source_path, contents.as_bytes())
 			.map_err(|err| Error::failed_to_write(&source_path, err))?;
 
-		fs::copy(&source_path, &build_cache_path)
-			.map_err(|err| Error::failed_to_copy(&source_path, &build_cache_path, err))?;
+		build_cache::copy_atomically(&source_path, &build_cache_path)?;
 
 		Ok(())
 	}
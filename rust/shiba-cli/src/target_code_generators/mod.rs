@@ -1,15 +1,17 @@
 mod api;
 pub mod executable;
 pub mod library;
+pub mod x11_executable;
 
 use crate::build::BuildOptions;
-use crate::compilation::{CompilationJobEmitter, Platform};
+use crate::compilation::{CompilationJobEmitter, Platform, System};
 use crate::compilation_data::Compilation;
 use crate::project_files::CodeMap;
 use crate::shader_data::ShaderSet;
 use crate::Result;
+use serde::Serialize;
 
-#[derive(Hash)]
+#[derive(Serialize)]
 pub struct GenerateTargetCodeOptions<'a> {
 	pub audio_codes: &'a CodeMap,
 	pub platform: Platform,
@@ -18,6 +20,14 @@ pub struct GenerateTargetCodeOptions<'a> {
 }
 
 pub trait TargetCodeGenerator: CompilationJobEmitter {
+	/// Names of the project codes which the generated code includes.
+	fn get_code_slots(&self) -> &'static [&'static str];
+
+	fn get_system(&self) -> System;
+
+	/// Names of the configured tools which the generated code uses.
+	fn get_tool_names(&self) -> Vec<&'static str>;
+
 	fn generate(
 		&self,
 		build_options: &BuildOptions,
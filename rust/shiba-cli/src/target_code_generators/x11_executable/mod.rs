@@ -0,0 +1,186 @@
+use super::api::{APIGenerator, APIGeneratorInputs, API};
+use super::{GenerateTargetCodeOptions, TargetCodeGenerator};
+use crate::build::{BuildOptions, BuildTarget};
+use crate::build_cache;
+use crate::compilation::{CompilationJobEmitter, System};
+use crate::compilation_data::{Compilation, CompilationJob, CompilationJobKind};
+use crate::project_data::Project;
+use crate::project_files::CodeMap;
+use crate::settings::RuntimeSettings;
+use crate::shader_data::{ShaderProgramMap, ShaderUniformArray};
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tera::{Context, Tera};
+
+pub struct X11ExecutableTargetCodeGenerator {
+	api_generator: APIGenerator,
+	development: bool,
+	glew_path: PathBuf,
+	tera: Tera,
+}
+
+impl X11ExecutableTargetCodeGenerator {
+	pub fn new(project: &Project) -> Result<Self> {
+		let api_generator = APIGenerator::new(&project.configuration)?;
+		let glew_path = project.configuration.get_path("glew");
+
+		let mut tera = Tera::default();
+
+		tera.add_raw_template("x11-executable", include_str!("./template.tera"))
+			.expect("Failed to add template.");
+
+		Ok(X11ExecutableTargetCodeGenerator {
+			api_generator,
+			development: project.development,
+			glew_path,
+			tera,
+		})
+	}
+}
+
+impl TargetCodeGenerator for X11ExecutableTargetCodeGenerator {
+	fn get_code_slots(&self) -> &'static [&'static str] {
+		&["declarations", "initialization", "rendering"]
+	}
+
+	// Without development, extensions are declared from the system headers.
+	fn get_tool_names(&self) -> Vec<&'static str> {
+		if self.development {
+			vec!["glew"]
+		} else {
+			vec![]
+		}
+	}
+
+	fn get_system(&self) -> System {
+		System::Linux
+	}
+
+	fn generate(
+		&self,
+		build_options: &BuildOptions,
+		options: &GenerateTargetCodeOptions,
+		compilation: &mut Compilation,
+	) -> Result<()> {
+		const OUTPUT_FILENAME: &str = "executable.cpp";
+
+		#[derive(Serialize)]
+		struct Inputs<'a> {
+			api_generator: APIGeneratorInputs<'a>,
+			development: bool,
+			glew_path: &'a Path,
+			options: &'a GenerateTargetCodeOptions<'a>,
+			runtime_settings: &'a RuntimeSettings,
+			target: BuildTarget,
+		}
+
+		let inputs = Inputs {
+			api_generator: self.api_generator.get_inputs(),
+			development: build_options.project.development,
+			glew_path: &self.glew_path,
+			options,
+			runtime_settings: &build_options.project.settings.runtime,
+			target: build_options.target,
+		};
+		let build_cache_entry = build_cache::lock_entry(
+			build_options,
+			"target-code-generators/x11-executable",
+			&inputs,
+		)?;
+		let build_cache_directory = build_cache_entry.get_directory();
+		let build_cache_path = build_cache_directory.join(OUTPUT_FILENAME);
+
+		compilation
+			.common
+			.link_dependencies
+			.insert(PathBuf::from("X11"));
+		compilation
+			.common
+			.link_dependencies
+			.insert(PathBuf::from("GL"));
+
+		if build_options.project.development {
+			compilation
+				.common
+				.link_dependencies
+				.insert(PathBuf::from("GLEW"));
+		}
+
+		let mut include_paths = BTreeSet::new();
+		if build_options.project.development {
+			include_paths.insert(self.glew_path.join("include"));
+		}
+
+		compilation.jobs.push(CompilationJob {
+			kind: CompilationJobKind::Cpp,
+			path: build_cache_path.clone(),
+			include_paths,
+		});
+
+		if build_cache_entry.is_hit(&build_cache_path) {
+			return Ok(());
+		}
+
+		let api = self.api_generator.generate(
+			options.project_codes,
+			options.shader_set,
+			build_options.project.development,
+			System::Linux,
+			BuildTarget::Executable,
+		)?;
+
+		#[derive(Serialize)]
+		struct OwnContext<'a> {
+			api: &'a API,
+			audio_codes: &'a CodeMap,
+			development: bool,
+			project_codes: &'a CodeMap,
+			runtime_settings: &'a RuntimeSettings,
+			shader_programs: &'a ShaderProgramMap,
+			shader_uniform_arrays: &'a [ShaderUniformArray],
+			target: BuildTarget,
+		}
+
+		let context = OwnContext {
+			api: &api,
+			audio_codes: &options.audio_codes,
+			development: build_options.project.development,
+			project_codes: &options.project_codes,
+			runtime_settings: &build_options.project.settings.runtime,
+			shader_programs: &options.shader_set.programs,
+			shader_uniform_arrays: &options.shader_set.uniform_arrays,
+			target: build_options.target,
+		};
+		let contents = self
+			.tera
+			.render(
+				"x11-executable",
+				&Context::from_serialize(&context).expect("Failed to create context."),
+			)
+			.map_err(|err| Error::failed_to_render_template("x11-executable", err))?;
+
+		let build_directory = build_cache_entry.create_scratch_directory()?;
+
+		let source_path = build_directory.join("executable.cpp");
+		fs::write(&source_path, contents.as_bytes())
+			.map_err(|err| Error::failed_to_write(&source_path, err))?;
+
+		let copy_from = build_directory.join("executable.cpp");
+		build_cache::copy_atomically(&copy_from, &build_cache_path)?;
+
+		Ok(())
+	}
+}
+
+impl CompilationJobEmitter for X11ExecutableTargetCodeGenerator {
+	fn requires_asm_compiler(&self) -> bool {
+		false
+	}
+
+	fn requires_cpp_compiler(&self) -> bool {
+		true
+	}
+}